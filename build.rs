@@ -0,0 +1,49 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Records the release channel zeus is built from, the branch of its checkout when that's one of
+// the channels. ZEUS_CHANNEL overrides it for builds outside a checkout, like packaged releases.
+
+use std::env;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+
+static CHANNELS: &'static [&'static str] = &["develop", "master"];
+
+// Feature branches come off develop
+static DEFAULT_CHANNEL: &'static str = "develop";
+
+fn main() {
+    let channel = env::var("ZEUS_CHANNEL").ok()
+        .or_else(checkout_branch)
+        .unwrap_or(String::from(DEFAULT_CHANNEL));
+
+    let out_dir = env::var("OUT_DIR").unwrap();
+    let mut file = File::create(&Path::new(&out_dir).join("channel.rs")).unwrap();
+    write!(file, "/// The release channel the running zeus was built from.\n\
+        pub static ZEUS_CHANNEL: &'static str = {:?};\n", channel).unwrap();
+}
+
+fn checkout_branch() -> Option<String> {
+    let output = match Command::new("git").arg("rev-parse").arg("--abbrev-ref").arg("HEAD")
+        .current_dir(env::var("CARGO_MANIFEST_DIR").unwrap()).output() {
+        Ok(ref output) if output.status.success() => output.stdout.clone(),
+        _ => return None
+    };
+
+    let branch = String::from_utf8_lossy(&output).trim().to_string();
+    if CHANNELS.contains(&&branch[..]) { Some(branch) } else { None }
+}
@@ -0,0 +1,236 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The cargo project zeus generates from Zeus.toml to compile the game.
+
+use std::ascii::AsciiExt;
+use std::fs::PathExt;
+use std::path::Path;
+use toml::Value;
+
+use dependency::Source;
+use features;
+use profile;
+use profile::Profile;
+use project::ZeusProject;
+use resolver::{Resolve, ResolvedPackage};
+use target::Target;
+
+/// Where the generated cargo project lives, relative to the project directory.
+pub static BUILD_DIR: &'static str = ".zeus/build";
+
+/// Where the Cargo.toml of every library from a zeus registry is generated, relative to
+/// BUILD_DIR. Packages ship without one, what they depend on is wherever the project put it.
+pub static PACKAGES_DIR: &'static str = "packages";
+
+// Paths in the generated manifest are relative to BUILD_DIR
+static PROJECT_ROOT: &'static str = "../..";
+
+// And those in the manifests of packages to PACKAGES_DIR/<name>
+static PACKAGE_PROJECT_ROOT: &'static str = "../../../..";
+
+// Packages without one are asset packs and the like, there's nothing for cargo to build
+static PACKAGE_LIB: &'static str = "src/lib.rs";
+
+static HEADER: &'static str =
+"# Generated by zeus from Zeus.toml, any changes will be overwritten.";
+
+/// What a single build asks for on top of Zeus.toml.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BuildOptions {
+    /// The profile from `[profile]` to build with.
+    pub profile: String,
+    /// Features to turn on besides the profile's and the default ones.
+    pub features: Vec<String>,
+    /// Whether the features `default` lists are on.
+    pub default_features: bool,
+    /// The triple to cross-compile for, the host when not set.
+    pub target: Option<String>
+}
+
+impl Default for BuildOptions {
+    fn default() -> BuildOptions {
+        BuildOptions {
+            profile: String::from(profile::DEFAULT_PROFILE),
+            features: Vec::new(),
+            default_features: true,
+            target: None
+        }
+    }
+}
+
+/// The crate name cargo will know the game by, cargo is picky about those.
+pub fn crate_name(game_name: &str) -> String {
+    let name: String = game_name.trim().chars()
+        .map(|c| if c.is_ascii() && c.is_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+
+    if name.is_empty() { String::from("game") } else { name }
+}
+
+/// The generated Cargo.toml, set up for building with a profile and the features the build
+/// turns on.
+pub fn cargo_manifest(project: &ZeusProject, profile: &Profile, enabled: &[String]) -> String {
+    let manifest = project.manifest();
+    let name = crate_name(project.game_name());
+    let version = manifest.lookup("game.version").and_then(|v| v.as_str()).unwrap_or("0.0.1");
+    let authors: Vec<String> = manifest.lookup("game.authors")
+        .and_then(|v| v.as_slice())
+        .map(|authors| authors.iter().filter_map(|a| a.as_str()).map(quote).collect())
+        .unwrap_or(Vec::new());
+
+    let mut lines = vec![
+        String::from(HEADER),
+        String::new(),
+        String::from("[package]"),
+        format!("name = {}", quote(&name)),
+        format!("version = {}", quote(version)),
+        format!("authors = [{}]", authors.join(", ")),
+        String::new(),
+        String::from("[[bin]]"),
+        format!("name = {}", quote(&name)),
+        format!("path = {}", project_path(PROJECT_ROOT, "src/main.rs")),
+        String::new(),
+        format!("[profile.{}]", profile.base),
+        format!("opt-level = {}", profile.opt_level),
+        format!("debug = {}", profile.debug),
+        String::new(),
+        String::from("[dependencies.athena]"),
+        format!("path = {}", project_path(PROJECT_ROOT, &project.package_path("athena").to_string_lossy()))
+    ];
+
+    for dependency in project.dependencies() {
+        if let Source::ZeusRegistry(_) = dependency.source {
+            if !is_library(project, &dependency.name) { continue; }
+        }
+
+        lines.push(String::new());
+        lines.push(format!("[dependencies.{}]", dependency.name));
+        lines.push(source_line(project, &dependency.name, &dependency.source, dependency.version.as_ref(),
+            PROJECT_ROOT, PACKAGES_DIR));
+    }
+
+    // The manifest is generated for every build, so what the build turns on simply is the default
+    let declared = features::features(manifest);
+    if !declared.is_empty() || !enabled.is_empty() {
+        lines.push(String::new());
+        lines.push(String::from("[features]"));
+        lines.push(format!("default = {}", quote_list(enabled)));
+        for (name, turns_on) in declared.iter().filter(|&(name, _)| name != features::DEFAULT_FEATURE) {
+            lines.push(format!("{} = {}", quote(name), quote_list(turns_on)));
+        }
+    }
+
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+/// The generated Cargo.toml of a library from a zeus registry, which lives in
+/// `PACKAGES_DIR/<name>`. Its dependencies are found through the project's resolve.
+pub fn package_manifest(project: &ZeusProject, package: &ResolvedPackage, resolve: &Resolve) -> String {
+    let version = package.version.as_ref().map(|v| v.to_string()).unwrap_or(String::from("0.0.0"));
+    let lib = project.package_path(&package.name).join(PACKAGE_LIB);
+
+    let mut lines = vec![
+        String::from(HEADER),
+        String::new(),
+        String::from("[package]"),
+        format!("name = {}", quote(&package.name)),
+        format!("version = {}", quote(&version)),
+        String::from("authors = []"),
+        String::new(),
+        String::from("[lib]"),
+        format!("path = {}", project_path(PACKAGE_PROJECT_ROOT, &lib.to_string_lossy())),
+        String::new(),
+        String::from("[dependencies.athena]"),
+        format!("path = {}", project_path(PACKAGE_PROJECT_ROOT, &project.package_path("athena").to_string_lossy()))
+    ];
+
+    for name in package.dependencies.iter().filter(|name| *name != "athena") {
+        let dependency = match resolve.package(name) {
+            Some(dependency) => dependency,
+            None => continue
+        };
+        if let Source::ZeusRegistry(_) = dependency.source {
+            if !is_library(project, name) { continue; }
+        }
+
+        lines.push(String::new());
+        lines.push(format!("[dependencies.{}]", name));
+        lines.push(source_line(project, name, &dependency.source, dependency.requirement.as_ref(),
+            PACKAGE_PROJECT_ROOT, ".."));
+    }
+
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+/// Whether a fetched package from a zeus registry is a library cargo builds.
+pub fn is_library(project: &ZeusProject, name: &str) -> bool {
+    project.get_file_path(project.package_path(name)).join(PACKAGE_LIB).exists()
+}
+
+/// Points cargo at vendored crates instead of crates.io and at the linker of the target being
+/// built for, none when neither is needed.
+pub fn cargo_config(project: &ZeusProject, target: Option<&Target>) -> Option<String> {
+    let mut lines = vec![String::from(HEADER)];
+
+    if let Some(crates_dir) = project.vendored_crates_dir() {
+        lines.push(String::new());
+        lines.push(String::from("[source.crates-io]"));
+        lines.push(String::from("replace-with = \"vendored\""));
+        lines.push(String::new());
+        lines.push(String::from("[source.vendored]"));
+        lines.push(format!("directory = {}", project_path(PROJECT_ROOT, &crates_dir.to_string_lossy())));
+    }
+
+    if let Some(&Target { ref triple, linker: Some(ref linker), .. }) = target {
+        lines.push(String::new());
+        lines.push(format!("[target.{}]", quote(triple)));
+        lines.push(format!("linker = {}", quote(linker)));
+    }
+
+    if lines.len() == 1 {
+        return None;
+    }
+
+    lines.push(String::new());
+    Some(lines.join("\n"))
+}
+
+fn quote_list(values: &[String]) -> String {
+    format!("[{}]", values.iter().map(|value| quote(value)).collect::<Vec<_>>().join(", "))
+}
+
+// Where cargo finds a dependency, from a manifest `root` away from the project and `packages`
+// away from the generated manifests of registry packages
+fn source_line(project: &ZeusProject, name: &str, source: &Source, version: Option<&String>, root: &str, packages: &str)
+    -> String
+{
+    match *source {
+        Source::Git { .. } => format!("path = {}", project_path(root, &project.package_path(name).to_string_lossy())),
+        Source::ZeusRegistry(_) => format!("path = {}", quote(&format!("{}/{}", packages, name))),
+        Source::Path(ref path) if path.is_absolute() => format!("path = {}", quote(&path.to_string_lossy())),
+        Source::Path(ref path) => format!("path = {}", project_path(root, &path.to_string_lossy())),
+        Source::Registry => format!("version = {}", quote(version.map(|v| &v[..]).unwrap_or("*")))
+    }
+}
+
+fn project_path(root: &str, path: &str) -> String {
+    quote(&format!("{}/{}", root, Path::new(path).to_string_lossy()))
+}
+
+fn quote(value: &str) -> String {
+    Value::String(String::from(value)).to_string()
+}
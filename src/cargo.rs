@@ -0,0 +1,107 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::env;
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use progress::{Phase, Progress, ProgressSink};
+use project::ZeusProjectError;
+use toolchain;
+use toolchain::Toolchain;
+
+static CARGO_PATH: &'static str = "cargo";
+
+/// Runs `cargo build` on a generated manifest. Cargo's output is passed on to the user and every
+/// crate it compiles is reported as a step, there's no telling how many there'll be. A pinned
+/// toolchain is picked through rustup.
+pub fn build(manifest_path: &Path, target_dir: &Path, args: &[String], vars: &[(String, String)],
+    toolchain: Option<&Toolchain>, progress: &ProgressSink) -> Result<(), ZeusProjectError>
+{
+    // Cargo runs next to the manifest so it finds the generated .cargo/config
+    let current_dir = try!(env::current_dir().map_err(|e| ZeusProjectError::io(manifest_path, e)));
+    let manifest_path = current_dir.join(manifest_path);
+    let build_dir = manifest_path.parent().expect("a manifest always lives in a directory");
+
+    let mut command = command(toolchain);
+    for &(ref key, ref value) in vars.iter() {
+        command.env(key, value);
+    }
+
+    let mut child = try!(command
+        .arg("build")
+        .arg("--manifest-path").arg(&manifest_path)
+        .args(args)
+        .env("CARGO_TARGET_DIR", current_dir.join(target_dir))
+        .current_dir(build_dir)
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| ZeusProjectError::io(Path::new(CARGO_PATH), e)));
+
+    // Cargo reports what it's doing on stderr, one crate per line
+    let mut compiled = 0;
+    for line in BufReader::new(child.stderr.take().expect("stderr is piped")).lines() {
+        let line = try!(line.map_err(|e| ZeusProjectError::io(Path::new(CARGO_PATH), e)));
+        let _ = writeln!(io::stderr(), "{}", line);
+
+        let line = line.trim_left();
+        if line.starts_with("Compiling ") {
+            compiled += 1;
+            progress.report(&Progress {
+                current: compiled,
+                ..Progress::new(Phase::Build, "", line)
+            });
+        }
+    }
+
+    let status = try!(child.wait().map_err(|e| ZeusProjectError::io(Path::new(CARGO_PATH), e)));
+
+    if status.success() {
+        return Ok(());
+    } else {
+        return Err(ZeusProjectError::CommandFailed {
+            command: format!("{} build --manifest-path {} {}", CARGO_PATH, manifest_path.display(), args.join(" ")),
+            output: String::from("See cargo's output above.")
+        });
+    }
+}
+
+/// Copies every crate a generated manifest needs from crates.io into a directory.
+pub fn vendor(manifest_path: &Path, vendor_dir: &Path, toolchain: Option<&Toolchain>) -> Result<(), ZeusProjectError> {
+    let status = try!(command(toolchain)
+        .arg("vendor")
+        .arg("--manifest-path").arg(manifest_path)
+        .arg(vendor_dir)
+        .status()
+        .map_err(|e| ZeusProjectError::io(Path::new(CARGO_PATH), e)));
+
+    if status.success() {
+        return Ok(());
+    } else {
+        return Err(ZeusProjectError::CommandFailed {
+            command: format!("{} vendor --manifest-path {} {}", CARGO_PATH, manifest_path.display(), vendor_dir.display()),
+            output: String::from("See cargo's output above.")
+        });
+    }
+}
+
+fn command(toolchain: Option<&Toolchain>) -> Command {
+    let mut command = Command::new(CARGO_PATH);
+    if let Some(toolchain) = toolchain {
+        command.env(toolchain::TOOLCHAIN_VAR, toolchain.name());
+    }
+    command
+}
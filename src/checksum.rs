@@ -0,0 +1,99 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fs;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use crypto::digest::Digest;
+use crypto::sha2::Sha256;
+
+use project::ZeusProjectError;
+
+/// Hashes a file, formatted as `sha256:<hex>`.
+pub fn sha256_file(path: &Path) -> Result<String, ZeusProjectError> {
+    let mut file = try!(File::open(path).map_err(|e| ZeusProjectError::io(path, e)));
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+
+    loop {
+        let read = try!(file.read(&mut buffer).map_err(|e| ZeusProjectError::io(path, e)));
+        if read == 0 { break; }
+        hasher.input(&buffer[..read]);
+    }
+
+    Ok(format!("sha256:{}", hasher.result_str()))
+}
+
+/// Hashes every file in a directory along with its path, formatted as `tree-sha256:<hex>`.
+/// Git metadata is left out so a checkout and a vendored copy of it hash the same. Symlinks
+/// are hashed by where they point, following them could loop or leave the directory.
+pub fn sha256_dir(path: &Path) -> Result<String, ZeusProjectError> {
+    let mut files = Vec::new();
+    try!(list_files(path, Path::new(""), &mut files));
+    files.sort();
+
+    let mut hasher = Sha256::new();
+    for relative in files.iter() {
+        // Paths are hashed the same way on every platform
+        let name: Vec<String> = relative.components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        let file = path.join(relative);
+        let metadata = try!(fs::symlink_metadata(&file).map_err(|e| ZeusProjectError::io(&file, e)));
+        let file_hash = if metadata.file_type().is_symlink() {
+            let target = try!(fs::read_link(&file).map_err(|e| ZeusProjectError::io(&file, e)));
+            format!("symlink:{}", target.to_string_lossy())
+        } else {
+            try!(sha256_file(&file))
+        };
+        hasher.input_str(&format!("{}\0{}\n", name.join("/"), file_hash));
+    }
+
+    Ok(format!("tree-sha256:{}", hasher.result_str()))
+}
+
+/// Fails loudly if a file isn't what it's supposed to be.
+pub fn verify_file(path: &Path, expected: &str) -> Result<(), ZeusProjectError> {
+    let actual = try!(sha256_file(path));
+    if actual != expected {
+        return Err(ZeusProjectError::ChecksumMismatch {
+            path: path.to_path_buf(),
+            expected: String::from(expected),
+            actual: actual
+        });
+    }
+
+    Ok(())
+}
+
+fn list_files(root: &Path, relative: &Path, files: &mut Vec<PathBuf>) -> Result<(), ZeusProjectError> {
+    let directory = root.join(relative);
+    let entries = try!(fs::read_dir(&directory).map_err(|e| ZeusProjectError::io(&directory, e)));
+
+    for entry in entries {
+        let entry = try!(entry.map_err(|e| ZeusProjectError::io(&directory, e)));
+        if entry.file_name() == ".git" { continue; }
+
+        let path = relative.join(entry.file_name());
+        let metadata = try!(fs::symlink_metadata(&entry.path()).map_err(|e| ZeusProjectError::io(&entry.path(), e)));
+        if metadata.is_dir() {
+            try!(list_files(root, &path, files));
+        } else {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,96 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use semver::{Version, VersionReq};
+
+/// The version of the running zeus.
+pub static ZEUS_VERSION: &'static str = env!("CARGO_PKG_VERSION");
+
+// ZEUS_CHANNEL, written by build.rs
+include!(concat!(env!("OUT_DIR"), "/channel.rs"));
+
+/// Release channels `zeus_version` may name instead of a version requirement.
+pub static ZEUS_CHANNELS: &'static [&'static str] = &["develop", "master"];
+
+static ZEUS_REPOSITORY: &'static str = "https://github.com/athena-org/zeus";
+
+/// The oldest rustc Athena builds with.
+pub static RUSTC_REQUIREMENT: &'static str = ">= 1.4.0";
+
+/// The oldest cargo Athena builds with.
+pub static CARGO_REQUIREMENT: &'static str = ">= 0.6.0";
+
+/// Athena uses unstable features, only this channel's compilers build it.
+pub static RUST_CHANNEL: &'static str = "nightly";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Compatibility {
+    Compatible,
+    /// The project follows another release channel, it will most likely still work.
+    OtherChannel(String),
+    /// The project needs a zeus release this one doesn't satisfy.
+    Incompatible(String)
+}
+
+/// Compares a project's `zeus_version` against the running zeus.
+pub fn check_zeus_version(requirement: &str) -> Compatibility {
+    let requirement = requirement.trim();
+
+    if ZEUS_CHANNELS.contains(&requirement) {
+        if requirement == ZEUS_CHANNEL {
+            return Compatibility::Compatible;
+        } else {
+            return Compatibility::OtherChannel(String::from(requirement));
+        }
+    }
+
+    let running = Version::parse(ZEUS_VERSION).ok().expect("the crate version is always valid semver");
+    match VersionReq::parse(requirement) {
+        Ok(ref req) if req.matches(&running) => Compatibility::Compatible,
+        _ => Compatibility::Incompatible(String::from(requirement))
+    }
+}
+
+/// Tells the user how to get a zeus matching the requirement.
+pub fn upgrade_hint(requirement: &str) -> String {
+    let requirement = requirement.trim();
+
+    if ZEUS_CHANNELS.contains(&requirement) {
+        format!("Build zeus from the `{}` branch of {} to match this project.", requirement, ZEUS_REPOSITORY)
+    } else {
+        format!("Install a zeus release matching `{}` from {}/releases, or change `zeus_version` if the project \
+            is known to work with zeus {}.", requirement, ZEUS_REPOSITORY, ZEUS_VERSION)
+    }
+}
+
+/// Checks a tool's version against one of the requirements above.
+pub fn tool_meets(version: &Version, requirement: &str) -> bool {
+    VersionReq::parse(requirement).map(|requirement| requirement.matches(version)).unwrap_or(false)
+}
+
+/// Reads the version out of a tool's `--version`, like `rustc 1.4.0-nightly (7e2d1cd 2015-08-05)`
+/// or `git version 2.5.0.windows.1`. Missing parts count as 0 and anything after the patch
+/// version is left out.
+pub fn parse_tool_version(output: &str) -> Option<Version> {
+    let word = match output.split_whitespace().find(|word| word.starts_with(|c: char| c.is_digit(10))) {
+        Some(word) => word,
+        None => return None
+    };
+
+    let mut parts = word.split(|c: char| !c.is_digit(10)).take_while(|part| !part.is_empty()).take(3)
+        .map(|part| part.to_string()).collect::<Vec<_>>();
+    while parts.len() < 3 { parts.push(String::from("0")); }
+
+    Version::parse(&parts.join(".")).ok()
+}
@@ -0,0 +1,309 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Settings that belong to the user or the machine rather than to a project's Zeus.toml. They
+//! come in layers, each overriding the ones before it:
+//!
+//! 1. built-in defaults
+//! 2. the user's config, `~/.zeus/config.toml`
+//! 3. the project's config, `.zeus/config.toml` in the project
+//! 4. `ZEUS_*` environment variables
+//! 5. command line flags
+//!
+//! Both config files are TOML and use the same keys, like `cache.dir` or `git.path`.
+
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use toml::{Parser, Table, Value};
+
+use project::ZeusProjectError;
+
+/// Where the user's config lives, relative to their home directory.
+pub static USER_CONFIG: &'static str = ".zeus/config.toml";
+
+/// Where a project's config lives, relative to the project. Unlike Zeus.toml it's for settings
+/// of the machine it's on, it isn't meant to be committed.
+pub static PROJECT_CONFIG: &'static str = ".zeus/config.toml";
+
+/// Repositories to fetch from somewhere else, original url to mirror url.
+pub static MIRRORS: &'static str = "mirrors";
+
+// ### Settings ###
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Kind {
+    String,
+    Path,
+    Boolean
+}
+
+/// A setting zeus knows about, anything else in a config file is listed but otherwise ignored.
+#[derive(Debug)]
+pub struct Known {
+    pub key: &'static str,
+    /// The environment variable that overrides it.
+    pub var: &'static str,
+    pub kind: Kind,
+    pub description: &'static str
+}
+
+pub static SETTINGS: &'static [Known] = &[
+    Known { key: "user.author", var: "ZEUS_AUTHOR", kind: Kind::String,
+        description: "The author written into new projects" },
+    Known { key: "cache.dir", var: "ZEUS_CACHE_DIR", kind: Kind::Path,
+        description: "Where downloads from registries are cached" },
+    Known { key: "git.path", var: "ZEUS_GIT", kind: Kind::Path,
+        description: "The git executable, found on the PATH when not set" },
+    Known { key: "vcs.backend", var: "ZEUS_VCS", kind: Kind::String,
+        description: "The version control backend, `cli` or `libgit2`" },
+    Known { key: "registry.default", var: "ZEUS_REGISTRY", kind: Kind::String,
+        description: "The registry searched when none is given" },
+    Known { key: "net.offline", var: "ZEUS_OFFLINE", kind: Kind::Boolean,
+        description: "Never touch the network, only use what's already fetched or cached" }
+];
+
+pub fn known(key: &str) -> Option<&'static Known> {
+    SETTINGS.iter().find(|setting| setting.key == key)
+}
+
+/// Which layer a setting came from.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Origin {
+    Default,
+    UserConfig(PathBuf),
+    ProjectConfig(PathBuf),
+    Environment(&'static str),
+    CommandLine
+}
+
+impl Display for Origin {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Origin::Default => write!(f, "default"),
+            Origin::UserConfig(ref path) | Origin::ProjectConfig(ref path) => write!(f, "file:{}", path.display()),
+            Origin::Environment(var) => write!(f, "env:{}", var),
+            Origin::CommandLine => write!(f, "command line")
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Setting {
+    /// The dotted key, parts that aren't plain words are quoted like `mirrors."https://a.b/c.git"`.
+    pub key: String,
+    pub value: Value,
+    pub origin: Origin
+}
+
+
+// ### Config ###
+
+/// Every setting that applies, after layering.
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    settings: BTreeMap<String, Setting>
+}
+
+impl Config {
+    /// Just the built-in defaults.
+    pub fn defaults() -> Config {
+        let mut config = Config::default();
+        if let Some(home) = env::home_dir() {
+            let cache = home.join(".zeus").join("cache").to_string_lossy().into_owned();
+            config.insert("cache.dir", Value::String(cache), Origin::Default);
+        }
+        config.insert("vcs.backend", Value::String(String::from("cli")), Origin::Default);
+        config.insert("net.offline", Value::Boolean(false), Origin::Default);
+        config
+    }
+
+    /// Layers the defaults, the user's config, the project's config when there is a project and
+    /// the environment. Command line flags are `set` on top by whoever parsed them.
+    pub fn load(project_dir: Option<&Path>) -> Result<Config, ZeusProjectError> {
+        let mut config = Config::defaults();
+
+        if let Some(path) = user_config_path() {
+            if let Some(table) = try!(read_table(&path)) {
+                try!(config.merge(&table, Origin::UserConfig(path)));
+            }
+        }
+
+        if let Some(project_dir) = project_dir {
+            let path = project_dir.join(PROJECT_CONFIG);
+            if let Some(table) = try!(read_table(&path)) {
+                try!(config.merge(&table, Origin::ProjectConfig(path)));
+            }
+        }
+
+        for setting in SETTINGS.iter() {
+            if let Some(raw) = env::var_os(setting.var) {
+                let raw = raw.to_string_lossy().into_owned();
+                // An empty variable is as good as an unset one
+                if raw.is_empty() { continue; }
+                try!(config.set(setting.key, &raw, Origin::Environment(setting.var)));
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Sets a setting from a raw string, read according to the setting's kind.
+    pub fn set(&mut self, key: &str, raw: &str, origin: Origin) -> Result<(), ZeusProjectError> {
+        let value = match known(key).map(|setting| setting.kind) {
+            Some(Kind::Boolean) => match &raw.to_lowercase()[..] {
+                "1" | "true" | "yes" | "on" => Value::Boolean(true),
+                "0" | "false" | "no" | "off" => Value::Boolean(false),
+                _ => return Err(invalid(key, &origin, "must be `true` or `false`"))
+            },
+            _ => Value::String(String::from(raw))
+        };
+
+        self.insert(key, value, origin);
+        Ok(())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Setting> {
+        self.settings.get(key)
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(|setting| setting.value.as_str())
+    }
+
+    pub fn get_bool(&self, key: &str) -> bool {
+        self.get(key).and_then(|setting| setting.value.as_bool()).unwrap_or(false)
+    }
+
+    /// Every setting in key order.
+    pub fn settings(&self) -> Vec<&Setting> {
+        self.settings.values().collect()
+    }
+
+    pub fn author(&self) -> Option<&str> {
+        self.get_str("user.author")
+    }
+
+    /// The root of zeus's cache, registries cache their files under it.
+    pub fn cache_dir(&self) -> Option<PathBuf> {
+        self.get_str("cache.dir").map(PathBuf::from)
+    }
+
+    pub fn offline(&self) -> bool {
+        self.get_bool("net.offline")
+    }
+
+    /// Every configured mirror, as original url and mirror url.
+    pub fn mirrors(&self) -> Vec<(String, String)> {
+        let prefix = format!("{}.", MIRRORS);
+        self.settings.values()
+            .filter(|setting| setting.key.starts_with(&prefix[..]))
+            .filter_map(|setting| setting.value.as_str().map(|mirror| {
+                (unquote(&setting.key[prefix.len()..]), String::from(mirror))
+            }))
+            .collect()
+    }
+
+    fn insert(&mut self, key: &str, value: Value, origin: Origin) {
+        self.settings.insert(String::from(key), Setting { key: String::from(key), value: value, origin: origin });
+    }
+
+    // Flattens a config file's tables into dotted keys, checking the ones zeus knows about
+    fn merge(&mut self, table: &Table, origin: Origin) -> Result<(), ZeusProjectError> {
+        let mut flat = Vec::new();
+        flatten("", table, &mut flat);
+
+        for (key, value) in flat {
+            if let Some(setting) = known(&key) {
+                let valid = match (setting.kind, &value) {
+                    (Kind::Boolean, &Value::Boolean(_)) => true,
+                    (Kind::String, &Value::String(_)) | (Kind::Path, &Value::String(_)) => true,
+                    _ => false
+                };
+                if !valid {
+                    let expected = if setting.kind == Kind::Boolean { "must be `true` or `false`" } else { "must be a string" };
+                    return Err(invalid(&key, &origin, expected));
+                }
+            }
+
+            self.insert(&key, value, origin.clone());
+        }
+
+        Ok(())
+    }
+}
+
+pub fn user_config_path() -> Option<PathBuf> {
+    env::home_dir().map(|home| home.join(USER_CONFIG))
+}
+
+/// Reads the user's config, not having one is fine.
+pub fn load_user_config() -> Result<Option<Value>, ZeusProjectError> {
+    match user_config_path() {
+        Some(path) => Ok(try!(read_table(&path)).map(Value::Table)),
+        None => Ok(None)
+    }
+}
+
+fn read_table(path: &Path) -> Result<Option<Table>, ZeusProjectError> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(ref e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(ZeusProjectError::io(path, e))
+    };
+
+    let mut source = String::new();
+    try!(file.read_to_string(&mut source).map_err(|e| ZeusProjectError::io(path, e)));
+
+    match Parser::new(&source).parse() {
+        Some(table) => Ok(Some(table)),
+        None => Err(ZeusProjectError::CorruptedFile(path.to_path_buf()))
+    }
+}
+
+fn invalid(key: &str, origin: &Origin, reason: &str) -> ZeusProjectError {
+    ZeusProjectError::InvalidSetting {
+        key: String::from(key),
+        origin: origin.to_string(),
+        reason: String::from(reason)
+    }
+}
+
+fn flatten(prefix: &str, table: &Table, flat: &mut Vec<(String, Value)>) {
+    for (name, value) in table.iter() {
+        let key = if prefix.is_empty() { quote(name) } else { format!("{}.{}", prefix, quote(name)) };
+        match *value {
+            Value::Table(ref inner) => flatten(&key, inner, flat),
+            _ => flat.push((key, value.clone()))
+        }
+    }
+}
+
+// Key parts that aren't plain words, like urls, are quoted the way TOML would quote them
+fn quote(name: &str) -> String {
+    let bare = !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+    if bare { String::from(name) } else { format!("\"{}\"", name.replace("\\", "\\\\").replace("\"", "\\\"")) }
+}
+
+fn unquote(part: &str) -> String {
+    if part.len() >= 2 && part.starts_with('"') && part.ends_with('"') {
+        part[1..part.len() - 1].replace("\\\"", "\"").replace("\\\\", "\\")
+    } else {
+        String::from(part)
+    }
+}
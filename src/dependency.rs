@@ -0,0 +1,277 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ascii::AsciiExt;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use semver::VersionReq;
+use toml::{Table, Value};
+
+use auth;
+use project::ZeusProjectError;
+
+/// Keys a dependency written as a table may use.
+pub static DEPENDENCY_KEYS: &'static [&'static str] =
+    &["git", "ref", "path", "registry", "version", "submodules", "lfs"];
+
+/// The branch git dependencies follow when they name neither a `ref` nor a `version`.
+pub static DEFAULT_GIT_REF: &'static str = "master";
+
+/// Where Athena is fetched from, Zeus.lock records packages by this url.
+pub static ATHENA_GIT: &'static str = "https://github.com/athena-org/athena.git";
+
+// ### Dependency ###
+
+/// Where a dependency comes from.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Source {
+    /// A git repository, checked out into `deps/` at a pinned branch or tag, or at the newest
+    /// tag matching the dependency's version requirement.
+    Git { url: String, reference: Option<String> },
+    /// A directory on disk, relative paths are relative to the project.
+    Path(PathBuf),
+    /// Cargo's own registry, cargo resolves and fetches these itself.
+    Registry,
+    /// A zeus package registry, a directory or an HTTP url. Packages are unpacked into `deps/`.
+    ZeusRegistry(String)
+}
+
+// Sources get printed, credentials in their urls don't
+impl Display for Source {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Source::Git { ref url, reference: Some(ref reference) } => write!(f, "{}#{}", auth::redact(url, &[]), reference),
+            Source::Git { ref url, reference: None } => write!(f, "{}", auth::redact(url, &[])),
+            Source::Path(ref path) => write!(f, "{}", path.display()),
+            Source::Registry => write!(f, "registry"),
+            Source::ZeusRegistry(ref registry) => write!(f, "registry {}", auth::redact(registry, &[]))
+        }
+    }
+}
+
+/// What to fetch along with a git repository, both are off unless asked for.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FetchOptions {
+    /// Check out the repository's submodules too.
+    pub submodules: bool,
+    /// Pull Git LFS objects, needs git-lfs installed.
+    pub lfs: bool
+}
+
+/// An Athena plugin, module or crate the game depends on, one entry of `[dependencies]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dependency {
+    pub name: String,
+    pub source: Source,
+    /// A semver requirement, picked against git tags or left to cargo for registry crates.
+    pub version: Option<String>,
+    pub fetch: FetchOptions
+}
+
+impl Dependency {
+    /// Reads a single `[dependencies]` entry, either a version string or a table with one of
+    /// `git`, `path` or `registry`, or just a `version`.
+    pub fn parse(name: &str, value: &Value) -> Result<Dependency, ZeusProjectError> {
+        let invalid = |reason: &str| ZeusProjectError::InvalidDependency {
+            name: String::from(name),
+            reason: String::from(reason)
+        };
+
+        try!(check_name(name).map_err(|r| invalid(&r)));
+
+        let table = match *value {
+            Value::String(ref version) => {
+                try!(check_requirement(version).map_err(|r| invalid(&r)));
+                return Ok(Dependency {
+                    name: String::from(name),
+                    source: Source::Registry,
+                    version: Some(version.clone()),
+                    fetch: FetchOptions::default()
+                });
+            },
+            Value::Table(ref table) => table,
+            _ => return Err(invalid("must be a version string or a table"))
+        };
+
+        let git = try!(get_str(table, "git").map_err(|r| invalid(&r)));
+        let path = try!(get_str(table, "path").map_err(|r| invalid(&r)));
+        let registry = try!(get_str(table, "registry").map_err(|r| invalid(&r)));
+        let reference = try!(get_str(table, "ref").map_err(|r| invalid(&r)));
+        let version = try!(get_str(table, "version").map_err(|r| invalid(&r)));
+        let fetch = try!(get_fetch_options(table).map_err(|r| invalid(&r)));
+
+        if let Some(version) = version {
+            try!(check_requirement(version).map_err(|r| invalid(&r)));
+        }
+
+        let source = match (git, path, registry) {
+            (Some(url), None, None) => {
+                if reference.is_some() && version.is_some() {
+                    return Err(invalid("`ref` and `version` can't be used together"));
+                }
+
+                // Without anything to go on follow the default branch
+                let reference = match (reference, version) {
+                    (None, None) => Some(DEFAULT_GIT_REF),
+                    (reference, _) => reference
+                };
+                Source::Git { url: String::from(url), reference: reference.map(String::from) }
+            },
+            (None, Some(path), None) => {
+                if version.is_some() { return Err(invalid("`version` can't be used with `path`")); }
+                Source::Path(PathBuf::from(path))
+            },
+            (None, None, Some(registry)) => {
+                if version.is_none() { return Err(invalid("packages from a `registry` need a `version`")); }
+                Source::ZeusRegistry(String::from(registry))
+            },
+            (None, None, None) => {
+                if version.is_none() { return Err(invalid("needs one of `git`, `path`, `registry` or `version`")); }
+                Source::Registry
+            },
+            _ => return Err(invalid("only one of `git`, `path` or `registry` may be used"))
+        };
+
+        if reference.is_some() && git.is_none() {
+            return Err(invalid("`ref` can only be used with `git`"));
+        }
+        if fetch != FetchOptions::default() && git.is_none() {
+            return Err(invalid("`submodules` and `lfs` can only be used with `git`"));
+        }
+
+        Ok(Dependency {
+            name: String::from(name),
+            source: source,
+            version: version.map(String::from),
+            fetch: fetch
+        })
+    }
+
+    /// Athena itself, as described by the `[athena]` table. A `version` that isn't a semver
+    /// requirement names a branch or tag, like `develop`.
+    pub fn athena(manifest: &Value) -> Dependency {
+        let version = manifest.lookup("athena.version")
+            .or_else(|| manifest.lookup("game.athena_version"))
+            .and_then(|v| v.as_str())
+            .unwrap_or("develop");
+
+        let (reference, version) = match VersionReq::parse(version) {
+            Ok(_) => (None, Some(String::from(version))),
+            Err(_) => (Some(String::from(version)), None)
+        };
+
+        // Anything wrong with these is reported by `zeus check-manifest`, here they're just off
+        let fetch = match manifest.lookup("athena") {
+            Some(&Value::Table(ref athena)) => get_fetch_options(athena).unwrap_or(FetchOptions::default()),
+            _ => FetchOptions::default()
+        };
+
+        Dependency {
+            name: String::from("athena"),
+            source: Source::Git { url: String::from(ATHENA_GIT), reference: reference },
+            version: version,
+            fetch: fetch
+        }
+    }
+
+    pub fn requirement(&self) -> Option<VersionReq> {
+        self.version.as_ref().and_then(|v| VersionReq::parse(v).ok())
+    }
+
+    /// The value this dependency is written as in `[dependencies]`.
+    pub fn to_value(&self) -> Value {
+        let mut table = Table::new();
+        match self.source {
+            Source::Registry => if let Some(ref version) = self.version {
+                return Value::String(version.clone());
+            },
+            Source::Git { ref url, ref reference } => {
+                table.insert(String::from("git"), Value::String(url.clone()));
+                if let Some(ref reference) = *reference {
+                    table.insert(String::from("ref"), Value::String(reference.clone()));
+                }
+            },
+            Source::Path(ref path) => {
+                table.insert(String::from("path"), Value::String(path.to_string_lossy().into_owned()));
+            },
+            Source::ZeusRegistry(ref registry) => {
+                table.insert(String::from("registry"), Value::String(registry.clone()));
+            }
+        }
+
+        if let Some(ref version) = self.version {
+            table.insert(String::from("version"), Value::String(version.clone()));
+        }
+        if self.fetch.submodules {
+            table.insert(String::from("submodules"), Value::Boolean(true));
+        }
+        if self.fetch.lfs {
+            table.insert(String::from("lfs"), Value::Boolean(true));
+        }
+
+        Value::Table(table)
+    }
+}
+
+/// Reads the whole `[dependencies]` table, in name order.
+pub fn parse_dependencies(table: &Table) -> Result<Vec<Dependency>, ZeusProjectError> {
+    let mut dependencies = Vec::new();
+    for (name, value) in table.iter() {
+        dependencies.push(try!(Dependency::parse(name, value)));
+    }
+
+    Ok(dependencies)
+}
+
+// Names become directories under deps/ and keys of the generated Cargo.toml, so they stick to
+// what cargo allows in crate names
+fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii() && (c.is_alphanumeric() || c == '_' || c == '-')) {
+        return Err(String::from("names may only use letters, digits, `_` and `-`"));
+    }
+    if name == "athena" {
+        return Err(String::from("`athena` is configured in the `[athena]` table, not as a dependency"));
+    }
+
+    Ok(())
+}
+
+fn check_requirement(version: &str) -> Result<(), String> {
+    match VersionReq::parse(version) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(format!("`{}` is not a valid version requirement", version))
+    }
+}
+
+fn get_str<'a>(table: &'a Table, key: &str) -> Result<Option<&'a str>, String> {
+    match table.get(key) {
+        Some(&Value::String(ref value)) => Ok(Some(value)),
+        Some(_) => Err(format!("`{}` must be a string", key)),
+        None => Ok(None)
+    }
+}
+
+fn get_fetch_options(table: &Table) -> Result<FetchOptions, String> {
+    let get_bool = |key: &str| match table.get(key) {
+        Some(&Value::Boolean(value)) => Ok(value),
+        Some(_) => Err(format!("`{}` must be `true` or `false`", key)),
+        None => Ok(false)
+    };
+
+    Ok(FetchOptions {
+        submodules: try!(get_bool("submodules")),
+        lfs: try!(get_bool("lfs"))
+    })
+}
@@ -0,0 +1,147 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Optional parts of the game, `[features]` in Zeus.toml. They work like cargo's: every feature
+//! lists what it turns on, other features of the game or features of a dependency written like
+//! `athena/editor`, and `default` lists what's on unless a build says otherwise.
+//!
+//! ```toml
+//! [features]
+//! default = ["debug-overlay"]
+//! debug-overlay = []
+//! editor = ["athena/editor"]
+//! telemetry = ["athena/telemetry"]
+//! ```
+
+use std::collections::BTreeMap;
+use toml::{Table, Value};
+
+use project::ZeusProjectError;
+
+/// The feature listing what's on by default.
+pub static DEFAULT_FEATURE: &'static str = "default";
+
+/// Every feature in the manifest and what it turns on, `default` included. Anything wrong with
+/// the table is reported by `zeus check-manifest`, here broken entries just aren't there.
+pub fn features(manifest: &Value) -> BTreeMap<String, Vec<String>> {
+    let mut features = BTreeMap::new();
+    if let Some(&Value::Table(ref table)) = manifest.lookup("features") {
+        for (name, value) in table.iter() {
+            if let Some(list) = as_list(value) {
+                features.insert(name.clone(), list);
+            }
+        }
+    }
+    features
+}
+
+/// Problems with a `[features]` table as feature and reason, `dependencies` being the names a
+/// feature may turn on features of.
+pub fn check(table: &Table, dependencies: &[&str]) -> Vec<(String, String)> {
+    let mut problems = Vec::new();
+
+    for (name, value) in table.iter() {
+        if name.contains('/') {
+            problems.push((name.clone(), String::from("feature names can't contain `/`")));
+        }
+
+        let list = match as_list(value) {
+            Some(list) => list,
+            None => {
+                problems.push((name.clone(), String::from("must be a list of feature names")));
+                continue;
+            }
+        };
+
+        for enabled in list.iter() {
+            if enabled == name {
+                problems.push((name.clone(), String::from("turns on itself")));
+            } else if let Err(reason) = check_name(enabled, table, dependencies) {
+                problems.push((name.clone(), reason));
+            }
+        }
+    }
+
+    problems
+}
+
+/// The features a build turns on: the default ones unless `default_features` is off, and the
+/// requested ones, which have to be in `[features]` or name a dependency.
+pub fn select(manifest: &Value, dependencies: &[&str], requested: &[String], default_features: bool)
+    -> Result<Vec<String>, ZeusProjectError>
+{
+    let empty = Table::new();
+    let table = match manifest.lookup("features") {
+        Some(&Value::Table(ref table)) => table,
+        _ => &empty
+    };
+
+    let mut selected = Vec::new();
+    if default_features {
+        selected.extend(features(manifest).remove(DEFAULT_FEATURE).unwrap_or(Vec::new()));
+    }
+
+    for feature in requested.iter() {
+        let feature = feature.trim();
+        if feature.is_empty() || feature == DEFAULT_FEATURE { continue; }
+
+        try!(check_name(feature, table, dependencies).map_err(|reason| ZeusProjectError::UnknownFeature {
+            name: String::from(feature),
+            reason: reason
+        }));
+        selected.push(String::from(feature));
+    }
+
+    let mut unique = Vec::new();
+    for feature in selected {
+        if !unique.contains(&feature) { unique.push(feature); }
+    }
+    Ok(unique)
+}
+
+// A feature of the game or, written like `athena/editor`, of one of its dependencies
+fn check_name(feature: &str, table: &Table, dependencies: &[&str]) -> Result<(), String> {
+    match feature.find('/') {
+        Some(slash) => {
+            let dependency = &feature[..slash];
+            if !dependencies.contains(&dependency) {
+                return Err(format!("`{}` names `{}`, which isn't a dependency", feature, dependency));
+            }
+            if feature[slash + 1..].is_empty() {
+                return Err(format!("`{}` doesn't name a feature of `{}`", feature, dependency));
+            }
+        },
+        None => if !table.contains_key(feature) || feature == DEFAULT_FEATURE {
+            let mut known: Vec<&str> = table.keys().map(|k| &k[..]).filter(|k| *k != DEFAULT_FEATURE).collect();
+            known.sort();
+            return Err(if known.is_empty() {
+                format!("`{}` isn't in `[features]`", feature)
+            } else {
+                format!("`{}` isn't in `[features]`, which has {}", feature, known.join(", "))
+            });
+        }
+    }
+
+    Ok(())
+}
+
+fn as_list(value: &Value) -> Option<Vec<String>> {
+    let values = match *value {
+        Value::Array(ref values) => values,
+        _ => return None
+    };
+
+    let list: Vec<String> = values.iter().filter_map(|v| v.as_str()).map(String::from).collect();
+    if list.len() == values.len() { Some(list) } else { None }
+}
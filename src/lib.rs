@@ -16,8 +16,29 @@
 #![feature(fs_walk)]
 #![feature(fs)]
 
+extern crate crypto;
+#[cfg(feature = "libgit2")]
+extern crate git2;
+extern crate semver;
+extern crate tar;
 extern crate toml;
 
+pub mod auth;
+pub mod build;
+pub mod compat;
+pub mod config;
+pub mod dependency;
+pub mod features;
+pub mod lockfile;
+pub mod manifest;
+pub mod profile;
+pub mod progress;
 pub mod project;
+pub mod registry;
+pub mod resolver;
+pub mod target;
+pub mod toolchain;
+pub mod vcs;
 
-mod git;
+mod cargo;
+mod checksum;
@@ -0,0 +1,180 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Zeus.lock, the resolved set of packages a project was last set up with.
+
+use std::fs::File;
+use std::io::{ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use semver::Version;
+use toml::{Parser, Value};
+
+use dependency::Source;
+use manifest::editor;
+use project::ZeusProjectError;
+use resolver::{Resolve, ResolvedPackage};
+
+pub static LOCKFILE: &'static str = "Zeus.lock";
+
+static HEADER: &'static str =
+"# This file is generated by zeus, do not edit it by hand.";
+
+/// Reads a lockfile, a missing one isn't an error since projects start out without one.
+pub fn load(path: &Path) -> Result<Option<Resolve>, ZeusProjectError> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(ref e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(ZeusProjectError::io(path, e))
+    };
+
+    let mut source = String::new();
+    try!(file.read_to_string(&mut source).map_err(|e| ZeusProjectError::io(path, e)));
+
+    match parse(&source) {
+        Some(resolve) => Ok(Some(resolve)),
+        None => Err(ZeusProjectError::CorruptedFile(path.to_path_buf()))
+    }
+}
+
+pub fn save(path: &Path, resolve: &Resolve) -> Result<(), ZeusProjectError> {
+    editor::replace_file(path, &render(resolve))
+}
+
+pub fn render(resolve: &Resolve) -> String {
+    let mut lines = vec![
+        String::from(HEADER),
+        format!("roots = {}", string_array(&resolve.roots))
+    ];
+
+    for package in resolve.packages.iter() {
+        lines.push(String::new());
+        lines.push(String::from("[[package]]"));
+        lines.push(format!("name = {}", quote(&package.name)));
+        lines.push(format!("source = {}", quote(&encode_source(&package.source))));
+        if let Some(ref version) = package.version {
+            lines.push(format!("version = {}", quote(&version.to_string())));
+        }
+        if let Some(ref reference) = package.reference {
+            lines.push(format!("reference = {}", quote(reference)));
+        }
+        if let Some(ref requirement) = package.requirement {
+            lines.push(format!("requirement = {}", quote(requirement)));
+        }
+        if let Some(ref commit) = package.commit {
+            lines.push(format!("commit = {}", quote(commit)));
+        }
+        lines.push(format!("dependencies = {}", string_array(&package.dependencies)));
+        if let Some(ref checksum) = package.checksum {
+            lines.push(format!("checksum = {}", quote(checksum)));
+        }
+    }
+
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+pub fn parse(source: &str) -> Option<Resolve> {
+    let root = match Parser::new(source).parse() {
+        Some(root) => Value::Table(root),
+        None => return None
+    };
+
+    let roots = match root.lookup("roots").and_then(strings) {
+        Some(roots) => roots,
+        None => return None
+    };
+
+    let mut packages = Vec::new();
+    for package in root.lookup("package").and_then(|p| p.as_slice()).unwrap_or(&[]) {
+        let name = match package.lookup("name").and_then(|v| v.as_str()) {
+            Some(name) => String::from(name),
+            None => return None
+        };
+        let source = match package.lookup("source").and_then(|v| v.as_str()).and_then(decode_source) {
+            Some(source) => source,
+            None => return None
+        };
+        let version = match package.lookup("version").and_then(|v| v.as_str()) {
+            Some(version) => match Version::parse(version) {
+                Ok(version) => Some(version),
+                Err(_) => return None
+            },
+            None => None
+        };
+
+        let reference = package.lookup("reference").and_then(|v| v.as_str()).map(String::from);
+
+        // Packages without a version were pinned to their reference in the manifest
+        let source = match source {
+            Source::Git { url, .. } if version.is_none() => Source::Git { url: url, reference: reference.clone() },
+            source => source
+        };
+
+        packages.push(ResolvedPackage {
+            name: name,
+            source: source,
+            version: version,
+            reference: reference,
+            requirement: package.lookup("requirement").and_then(|v| v.as_str()).map(String::from),
+            commit: package.lookup("commit").and_then(|v| v.as_str()).map(String::from),
+            dependencies: package.lookup("dependencies").and_then(strings).unwrap_or(Vec::new()),
+            checksum: package.lookup("checksum").and_then(|v| v.as_str()).map(String::from)
+        });
+    }
+
+    Some(Resolve {
+        roots: roots,
+        packages: packages
+    })
+}
+
+
+// ### Helpers ###
+
+fn encode_source(source: &Source) -> String {
+    match *source {
+        Source::Git { ref url, .. } => format!("git+{}", url),
+        Source::Path(ref path) => format!("path+{}", path.display()),
+        Source::Registry => String::from("registry"),
+        Source::ZeusRegistry(ref registry) => format!("zeus+{}", registry)
+    }
+}
+
+// The pinned reference lives in its own key, only the location is encoded here
+fn decode_source(source: &str) -> Option<Source> {
+    if source.starts_with("git+") {
+        Some(Source::Git { url: String::from(&source[4..]), reference: None })
+    } else if source.starts_with("path+") {
+        Some(Source::Path(PathBuf::from(&source[5..])))
+    } else if source.starts_with("zeus+") {
+        Some(Source::ZeusRegistry(String::from(&source[5..])))
+    } else if source == "registry" {
+        Some(Source::Registry)
+    } else {
+        None
+    }
+}
+
+fn strings(value: &Value) -> Option<Vec<String>> {
+    value.as_slice().map(|values| values.iter().filter_map(|v| v.as_str()).map(String::from).collect())
+}
+
+fn string_array(values: &[String]) -> String {
+    let values: Vec<String> = values.iter().map(|v| quote(v)).collect();
+    format!("[{}]", values.join(", "))
+}
+
+fn quote(value: &str) -> String {
+    Value::String(String::from(value)).to_string()
+}
@@ -0,0 +1,531 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cmp;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use semver::{Version, VersionReq};
+use toml::{Parser, Table, Value};
+
+use compat::ZEUS_CHANNELS;
+use dependency::{Dependency, DEPENDENCY_KEYS};
+use features;
+use manifest::{Diagnostic, Severity};
+use manifest::migrate::CURRENT_VERSION;
+use manifest::text::{header_name, is_header, line_key};
+use profile::{Profile, PROFILE_KEYS};
+use project::ZeusProjectError;
+use target::{Target, TARGET_KEYS};
+use toolchain::{Toolchain, TOOLCHAIN_KEYS};
+
+static ROOT_KEYS: &'static [&'static str] = &["manifest_version", "game", "athena", "dependencies", "vendor", "toolchain", "profile", "features", "target"];
+static GAME_KEYS: &'static [&'static str] =
+    &["name", "version", "zeus_version", "athena_version", "authors", "description", "keywords"];
+static ATHENA_KEYS: &'static [&'static str] = &["version", "submodules", "lfs"];
+static VENDOR_KEYS: &'static [&'static str] = &["directory", "crates"];
+
+// ### Entry Points ###
+
+pub fn check_file(path: &Path) -> Result<Vec<Diagnostic>, ZeusProjectError> {
+    let mut file = try!(File::open(path).map_err(|e| ZeusProjectError::io(path, e)));
+    let mut source = String::new();
+    try!(file.read_to_string(&mut source).map_err(|e| ZeusProjectError::io(path, e)));
+
+    Ok(check_str(&source))
+}
+
+/// Reports every problem in a manifest at once instead of stopping at the first one.
+pub fn check_str(source: &str) -> Vec<Diagnostic> {
+    let mut checker = Checker {
+        source: source,
+        diagnostics: Vec::new()
+    };
+
+    let mut parser = Parser::new(source);
+    match parser.parse() {
+        Some(root) => checker.check_root(&root),
+        None => {
+            // Without a syntax tree nothing else can be checked
+            for error in parser.errors.iter() {
+                let (line, column) = parser.to_linecol(error.lo);
+                checker.push(Severity::Error, (line + 1, column + 1), error.desc.clone());
+            }
+        }
+    }
+
+    checker.diagnostics
+}
+
+
+// ### Checker ###
+
+struct Checker<'a> {
+    source: &'a str,
+    diagnostics: Vec<Diagnostic>
+}
+
+impl<'a> Checker<'a> {
+    fn check_root(&mut self, root: &Table) {
+        for (key, value) in root.iter() {
+            if !ROOT_KEYS.contains(&&key[..]) {
+                let message = match *value {
+                    Value::Table(_) => format!("unknown table `[{}]`", key),
+                    _ => format!("unknown key `{}`", key)
+                };
+                let message = with_suggestion(message, key, ROOT_KEYS);
+                let position = self.position("", Some(key));
+                self.push(Severity::Error, position, message);
+            }
+        }
+
+        let manifest_version = match root.get("manifest_version") {
+            Some(&Value::Integer(version)) => version,
+            Some(_) => {
+                self.error("", Some("manifest_version"), String::from("`manifest_version` must be an integer"));
+                CURRENT_VERSION
+            },
+            None => 1
+        };
+        if manifest_version > CURRENT_VERSION {
+            self.error("", Some("manifest_version"), format!(
+                "schema version {} is newer than this zeus understands (up to {})", manifest_version, CURRENT_VERSION));
+        } else if manifest_version < CURRENT_VERSION {
+            let position = self.position("", Some("manifest_version"));
+            self.push(Severity::Warning, position, format!(
+                "schema version {} is outdated, run `zeus migrate` to update to version {}",
+                manifest_version, CURRENT_VERSION));
+        }
+
+        match root.get("game") {
+            Some(&Value::Table(ref game)) => self.check_game(game, manifest_version),
+            Some(_) => self.error("", Some("game"), String::from("`game` must be a table")),
+            None => self.error("", None, String::from("missing the `[game]` table"))
+        }
+
+        match root.get("athena") {
+            Some(&Value::Table(ref athena)) => self.check_athena(athena),
+            Some(_) => self.error("", Some("athena"), String::from("`athena` must be a table")),
+            None => {}
+        }
+
+        match root.get("dependencies") {
+            Some(&Value::Table(ref dependencies)) => self.check_dependencies(dependencies),
+            Some(_) => self.error("", Some("dependencies"), String::from("`dependencies` must be a table")),
+            None => {}
+        }
+
+        match root.get("vendor") {
+            Some(&Value::Table(ref vendor)) => self.check_vendor(vendor),
+            Some(_) => self.error("", Some("vendor"), String::from("`vendor` must be a table")),
+            None => {}
+        }
+
+        match root.get("toolchain") {
+            Some(&Value::Table(ref toolchain)) => self.check_toolchain(toolchain),
+            Some(_) => self.error("", Some("toolchain"), String::from("`toolchain` must be a table")),
+            None => {}
+        }
+
+        match root.get("features") {
+            Some(&Value::Table(ref table)) => {
+                let mut dependencies = vec!["athena"];
+                if let Some(&Value::Table(ref declared)) = root.get("dependencies") {
+                    dependencies.extend(declared.keys().map(|name| &name[..]));
+                }
+
+                for (feature, reason) in features::check(table, &dependencies) {
+                    self.error("features", Some(&feature), format!("feature `{}`: {}", feature, reason));
+                }
+            },
+            Some(_) => self.error("", Some("features"), String::from("`features` must be a table")),
+            None => {}
+        }
+
+        match root.get("profile") {
+            Some(&Value::Table(ref profiles)) => self.check_profiles(profiles),
+            Some(_) => self.error("", Some("profile"), String::from("`profile` must be a table")),
+            None => {}
+        }
+
+        match root.get("target") {
+            Some(&Value::Table(ref targets)) => self.check_targets(targets),
+            Some(_) => self.error("", Some("target"), String::from("`target` must be a table")),
+            None => {}
+        }
+    }
+
+    fn check_profiles(&mut self, profiles: &Table) {
+        for (name, value) in profiles.iter() {
+            let table_name = format!("profile.{}", name);
+            match *value {
+                Value::Table(ref table) => self.check_known_keys(&table_name, table, PROFILE_KEYS),
+                _ => {
+                    self.error("profile", Some(name), format!("`{}` must be a table", table_name));
+                    continue;
+                }
+            }
+
+            // Resolving covers both the profile's own values and everything it inherits
+            if let Err(reason) = Profile::resolve_in(Some(profiles), name) {
+                self.error(&table_name, None, format!("profile `{}`: {}", name, reason));
+            }
+        }
+    }
+
+    fn check_targets(&mut self, targets: &Table) {
+        for (triple, value) in targets.iter() {
+            let table_name = format!("target.{}", triple);
+            match *value {
+                Value::Table(ref table) => {
+                    self.check_known_keys(&table_name, table, TARGET_KEYS);
+                    if let Err(reason) = Target::parse(triple, table) {
+                        self.error(&table_name, None, format!("target `{}`: {}", triple, reason));
+                    }
+                },
+                _ => self.error("target", Some(triple), format!("`{}` must be a table", table_name))
+            }
+        }
+    }
+
+    fn check_toolchain(&mut self, toolchain: &Table) {
+        self.check_known_keys("toolchain", toolchain, TOOLCHAIN_KEYS);
+
+        if let Err(reason) = Toolchain::parse(toolchain) {
+            // Point at the key that's wrong when the reason names one
+            let key = TOOLCHAIN_KEYS.iter().find(|key| reason.contains(&format!("`{}`", key)[..]) && toolchain.contains_key(**key));
+            self.error("toolchain", key.map(|key| *key), format!("toolchain {}", reason));
+        }
+    }
+
+    fn check_vendor(&mut self, vendor: &Table) {
+        self.check_known_keys("vendor", vendor, VENDOR_KEYS);
+
+        if let Some(directory) = self.required_str(vendor, "vendor", "directory") {
+            if directory.trim().is_empty() {
+                self.error("vendor", Some("directory"), String::from("`directory` must not be empty"));
+            }
+        }
+
+        match vendor.get("crates") {
+            Some(&Value::Boolean(_)) | None => {},
+            Some(_) => self.error("vendor", Some("crates"), String::from("`crates` must be `true` or `false`"))
+        }
+    }
+
+    fn check_dependencies(&mut self, dependencies: &Table) {
+        for (name, value) in dependencies.iter() {
+            if let Value::Table(ref table) = *value {
+                let table_name = format!("dependencies.{}", name);
+                self.check_known_keys(&table_name, table, DEPENDENCY_KEYS);
+            }
+
+            match Dependency::parse(name, value) {
+                Err(ZeusProjectError::InvalidDependency { ref reason, .. }) =>
+                    self.error("dependencies", Some(name), format!("dependency `{}` {}", name, reason)),
+                _ => {}
+            }
+        }
+    }
+
+    fn check_athena(&mut self, athena: &Table) {
+        self.check_known_keys("athena", athena, ATHENA_KEYS);
+
+        if let Some(version) = self.optional_str(athena, "athena", "version") {
+            if version.trim().is_empty() {
+                self.error("athena", Some("version"), String::from("`version` must not be empty"));
+            }
+        }
+
+        for key in ["submodules", "lfs"].iter() {
+            match athena.get(*key) {
+                Some(&Value::Boolean(_)) | None => {},
+                Some(_) => self.error("athena", Some(*key), format!("`{}` must be `true` or `false`", key))
+            }
+        }
+    }
+
+    fn check_game(&mut self, game: &Table, manifest_version: i64) {
+        self.check_known_keys("game", game, GAME_KEYS);
+
+        if let Some(name) = self.required_str(game, "game", "name") {
+            if name.trim().is_empty() {
+                self.error("game", Some("name"), String::from("`name` must not be empty"));
+            }
+        }
+
+        if let Some(version) = self.required_str(game, "game", "version") {
+            if Version::parse(version).is_err() {
+                self.error("game", Some("version"),
+                    format!("`{}` is not a valid semantic version, expected something like `0.1.0`", version));
+            }
+        }
+
+        if let Some(zeus_version) = self.optional_str(game, "game", "zeus_version") {
+            if !ZEUS_CHANNELS.contains(&zeus_version) && VersionReq::parse(zeus_version).is_err() {
+                self.error("game", Some("zeus_version"),
+                    format!("`{}` is not a zeus version requirement or one of the channels {}",
+                        zeus_version, ZEUS_CHANNELS.join(", ")));
+            }
+        }
+
+        if manifest_version >= 2 && game.contains_key("athena_version") {
+            self.error("game", Some("athena_version"),
+                String::from("`athena_version` has moved to `version` in the `[athena]` table"));
+        } else if let Some(athena_version) = self.optional_str(game, "game", "athena_version") {
+            if athena_version.trim().is_empty() {
+                self.error("game", Some("athena_version"), String::from("`athena_version` must not be empty"));
+            }
+        }
+
+        match game.get("authors") {
+            Some(&Value::Array(ref authors)) => {
+                for author in authors.iter() {
+                    match author.as_str() {
+                        Some(author) => if let Some(problem) = author_problem(author) {
+                            self.error("game", Some("authors"), format!("author `{}` {}", author, problem));
+                        },
+                        None => self.error("game", Some("authors"), String::from("every author must be a string"))
+                    }
+                }
+            },
+            Some(_) => self.error("game", Some("authors"), String::from("`authors` must be an array of strings")),
+            None => {}
+        }
+
+        self.optional_str(game, "game", "description");
+
+        match game.get("keywords") {
+            Some(&Value::Array(ref keywords)) => if keywords.iter().any(|k| k.as_str().is_none()) {
+                self.error("game", Some("keywords"), String::from("every keyword must be a string"));
+            },
+            Some(_) => self.error("game", Some("keywords"), String::from("`keywords` must be an array of strings")),
+            None => {}
+        }
+    }
+
+
+    // ## Helpers ##
+
+    fn check_known_keys(&mut self, table: &str, values: &Table, known: &[&str]) {
+        for key in values.keys() {
+            if !known.contains(&&key[..]) {
+                let message = with_suggestion(format!("unknown key `{}` in `[{}]`", key, table), key, known);
+                self.error(table, Some(key), message);
+            }
+        }
+    }
+
+    fn required_str<'t>(&mut self, values: &'t Table, table: &str, key: &str) -> Option<&'t str> {
+        if !values.contains_key(key) {
+            self.error(table, None, format!("missing `{}` in `[{}]`", key, table));
+            return None;
+        }
+
+        self.optional_str(values, table, key)
+    }
+
+    fn optional_str<'t>(&mut self, values: &'t Table, table: &str, key: &str) -> Option<&'t str> {
+        match values.get(key) {
+            Some(&Value::String(ref value)) => Some(value),
+            Some(_) => {
+                self.error(table, Some(key), format!("`{}` must be a string", key));
+                None
+            },
+            None => None
+        }
+    }
+
+    fn error(&mut self, table: &str, key: Option<&str>, message: String) {
+        let position = self.position(table, key);
+        self.push(Severity::Error, position, message);
+    }
+
+    fn push(&mut self, severity: Severity, position: (usize, usize), message: String) {
+        self.diagnostics.push(Diagnostic {
+            severity: severity,
+            line: position.0,
+            column: position.1,
+            message: message
+        });
+    }
+
+    /// Finds where a key (or the table header itself) is written, the parsed table doesn't keep positions.
+    fn position(&self, table: &str, key: Option<&str>) -> (usize, usize) {
+        let mut current = String::new();
+        let mut header = None;
+
+        // A key holding a table may also be written as a `[table.key]` header
+        let full_key = match key {
+            Some(key) if table.is_empty() => Some(String::from(key)),
+            Some(key) => Some(format!("{}.{}", table, key)),
+            None => None
+        };
+
+        for (index, line) in self.source.lines().enumerate() {
+            let trimmed = line.trim_left();
+            let position = (index + 1, line.len() - trimmed.len() + 1);
+
+            if is_header(trimmed) {
+                current = header_name(trimmed);
+                if full_key.as_ref() == Some(&current) { return position; }
+                if current == table && header.is_none() {
+                    header = Some(position);
+                    if key.is_none() { return position; }
+                }
+            } else if current == table && key.is_some() && line_key(trimmed) == key {
+                return position;
+            }
+        }
+
+        header.unwrap_or((1, 1))
+    }
+}
+
+
+// ### Helpers ###
+
+fn author_problem(author: &str) -> Option<&'static str> {
+    let author = author.trim();
+    if author.is_empty() { return Some("is empty"); }
+
+    match (author.find('<'), author.rfind('>')) {
+        (None, None) => None,
+        (Some(open), Some(close)) if open < close && close == author.len() - 1 => {
+            let email = &author[open + 1..close];
+            if author[..open].trim().is_empty() {
+                Some("has no name before the email address")
+            } else if !email.contains('@') || email.contains('<') || email.contains(' ') {
+                Some("has an invalid email address")
+            } else {
+                None
+            }
+        },
+        _ => Some("should look like `Jane Doe <jane@example.com>`")
+    }
+}
+
+fn with_suggestion(message: String, key: &str, known: &[&str]) -> String {
+    let closest = known.iter()
+        .map(|candidate| (distance(key, candidate), *candidate))
+        .filter(|&(distance, _)| distance <= 3)
+        .min();
+
+    match closest {
+        Some((_, candidate)) => format!("{}, did you mean `{}`?", message, candidate),
+        None => message
+    }
+}
+
+// Levenshtein distance, good enough to catch typos like `athena_verison`
+fn distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..b.len() + 1).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for j in 0..b.len() {
+            let above = row[j + 1];
+            row[j + 1] = if a_char == b[j] {
+                diagonal
+            } else {
+                1 + cmp::min(diagonal, cmp::min(row[j], above))
+            };
+            diagonal = above;
+        }
+    }
+
+    row[b.len()]
+}
+
+
+#[cfg(test)]
+mod tests {
+    use manifest::Diagnostic;
+    use super::check_str;
+
+    static HEADER: &'static str = "manifest_version = 2\n\n[game]\nname = \"test\"\n";
+
+    fn errors(source: &str) -> Vec<Diagnostic> {
+        check_str(source).into_iter().filter(|d| d.is_error()).collect()
+    }
+
+    #[test]
+    fn a_valid_manifest_has_no_diagnostics() {
+        assert!(check_str(&format!("{}version = \"0.1.0\"\n", HEADER)).is_empty());
+    }
+
+    #[test]
+    fn diagnostics_point_at_the_key() {
+        let errors = errors(&format!("{}  version = \"0.1\"\n", HEADER));
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!((errors[0].line, errors[0].column), (5, 3));
+        assert!(errors[0].message.contains("not a valid semantic version"));
+    }
+
+    #[test]
+    fn missing_keys_point_at_their_table() {
+        let errors = errors("manifest_version = 2\n\n[game]\nversion = \"0.1.0\"\n");
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!((errors[0].line, errors[0].column), (3, 1));
+        assert_eq!(errors[0].message, "missing `name` in `[game]`");
+    }
+
+    #[test]
+    fn syntax_errors_have_positions() {
+        let errors = errors("[game]\nname = \"test\"\nversion = \n");
+
+        assert!(!errors.is_empty());
+        assert_eq!(errors[0].line, 3);
+    }
+
+    #[test]
+    fn typos_get_a_suggestion() {
+        let errors = errors(&format!("{}version = \"0.1.0\"\nathena_verison = \"develop\"\n", HEADER));
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 6);
+        assert_eq!(errors[0].message, "unknown key `athena_verison` in `[game]`, did you mean `athena_version`?");
+    }
+
+    #[test]
+    fn unrelated_keys_get_no_suggestion() {
+        let errors = errors(&format!("{}version = \"0.1.0\"\nsomething = 1\n", HEADER));
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "unknown key `something` in `[game]`");
+    }
+
+    #[test]
+    fn features_of_unknown_dependencies_are_reported() {
+        let errors = errors(&format!("{}version = \"0.1.0\"\n\n[features]\nphysics = [\"rapier/simd\"]\n", HEADER));
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!((errors[0].line, errors[0].column), (8, 1));
+        assert_eq!(errors[0].message, "feature `physics`: `rapier/simd` names `rapier`, which isn't a dependency");
+    }
+
+    #[test]
+    fn invalid_dependencies_are_reported_at_their_name() {
+        let errors = errors(&format!("{}version = \"0.1.0\"\n\n[dependencies]\nrapier = \"not a version\"\n", HEADER));
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!((errors[0].line, errors[0].column), (8, 1));
+        assert_eq!(errors[0].message, "dependency `rapier` `not a version` is not a valid version requirement");
+    }
+}
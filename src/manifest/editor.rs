@@ -0,0 +1,506 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::ffi::OsString;
+use std::fs;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use toml::{Parser, Table, Value};
+
+use manifest::text::{comment_start, header_name, is_header, line_key, value_end};
+use project::ZeusProjectError;
+
+/// Edits a manifest in place. Only the lines holding the edited keys are rewritten, comments,
+/// ordering and formatting everywhere else are kept as they are.
+///
+/// Keys are dotted paths like `athena.version` or `dependencies.physics.git`.
+#[derive(Debug, Clone)]
+pub struct ManifestEditor {
+    lines: Vec<String>,
+    trailing_newline: bool
+}
+
+impl ManifestEditor {
+    // ## Constructors ##
+
+    pub fn new(source: &str) -> ManifestEditor {
+        ManifestEditor {
+            lines: source.lines().map(String::from).collect(),
+            trailing_newline: source.ends_with('\n')
+        }
+    }
+
+    pub fn open(path: &Path) -> Result<ManifestEditor, ZeusProjectError> {
+        let mut file = try!(File::open(path).map_err(|e| ZeusProjectError::io(path, e)));
+        let mut source = String::new();
+        try!(file.read_to_string(&mut source).map_err(|e| ZeusProjectError::io(path, e)));
+
+        Ok(ManifestEditor::new(&source))
+    }
+
+    /// Writes the manifest out next to `path` and moves it over it once it's known to be valid
+    /// TOML, so a failed save never leaves a broken manifest behind.
+    pub fn save(&self, path: &Path) -> Result<(), ZeusProjectError> {
+        let source = self.to_string();
+        let mut parser = Parser::new(&source);
+        if parser.parse().is_none() {
+            return Err(ZeusProjectError::InvalidManifest { path: path.to_path_buf(), errors: parser.errors.len() });
+        }
+
+        replace_file(path, &source)
+    }
+
+
+    // ## Queries ##
+
+    pub fn get(&self, key: &str) -> Option<Value> {
+        let root = match Parser::new(&self.to_string()).parse() {
+            Some(root) => Value::Table(root),
+            None => return None
+        };
+
+        root.lookup(key).cloned()
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.has_table(key) || self.get(key).is_some()
+    }
+
+
+    // ## Edits ##
+
+    pub fn set(&mut self, key: &str, value: Value) -> Result<(), ZeusProjectError> {
+        let parts = try!(split_key(key));
+        let (table, name) = (parts[..parts.len() - 1].join("."), parts[parts.len() - 1]);
+
+        // Replacing a whole table, drop the old section and write the new value as a key
+        if self.has_table(key) {
+            self.remove_table(key);
+        }
+
+        // The key is already assigned, rewrite just its value
+        if let Some((start, end)) = self.find_key(&table, name) {
+            self.replace_value(start, end, &value);
+            return Ok(());
+        }
+
+        // The key lives inside an inline table further up
+        for split in (1..parts.len() - 1).rev() {
+            let outer_table = parts[..split].join(".");
+            if let Some((start, end)) = self.find_key(&outer_table, parts[split]) {
+                let mut outer = match self.get(&parts[..split + 1].join(".")) {
+                    Some(Value::Table(outer)) => outer,
+                    _ => return Err(ZeusProjectError::InvalidKey(String::from(key)))
+                };
+                if !insert_nested(&mut outer, &parts[split + 1..], value) {
+                    return Err(ZeusProjectError::InvalidKey(String::from(key)));
+                }
+
+                self.replace_value(start, end, &Value::Table(outer));
+                return Ok(());
+            }
+        }
+
+        // A value of some parent is in the way, e.g. `game.name.first`
+        for split in 1..parts.len() - 1 {
+            match self.get(&parts[..split].join(".")) {
+                Some(Value::Table(_)) | None => {},
+                Some(_) => return Err(ZeusProjectError::InvalidKey(String::from(key)))
+            }
+        }
+
+        // A brand new key
+        let line = format!("{} = {}", format_key(name), format_value(&value));
+        if table.is_empty() || self.has_table(&table) {
+            let index = self.insert_point(&table);
+            self.lines.insert(index, line);
+
+            // Keep top level keys visually apart from the first table
+            if index + 1 < self.lines.len() && is_header(&self.lines[index + 1]) {
+                self.lines.insert(index + 1, String::new());
+            }
+        } else {
+            self.append_table(&table, line);
+        }
+
+        Ok(())
+    }
+
+    /// Removes a key or a whole table, returns false if there was nothing to remove.
+    pub fn remove(&mut self, key: &str) -> Result<bool, ZeusProjectError> {
+        let parts = try!(split_key(key));
+
+        if self.has_table(key) {
+            self.remove_table(key);
+            return Ok(true);
+        }
+
+        let (table, name) = (parts[..parts.len() - 1].join("."), parts[parts.len() - 1]);
+        if let Some((start, end)) = self.find_key(&table, name) {
+            for _ in start..end + 1 {
+                self.lines.remove(start);
+            }
+            return Ok(true);
+        }
+
+        for split in (1..parts.len() - 1).rev() {
+            let outer_table = parts[..split].join(".");
+            if let Some((start, end)) = self.find_key(&outer_table, parts[split]) {
+                let mut outer = match self.get(&parts[..split + 1].join(".")) {
+                    Some(Value::Table(outer)) => outer,
+                    _ => return Ok(false)
+                };
+                if !remove_nested(&mut outer, &parts[split + 1..]) {
+                    return Ok(false);
+                }
+
+                self.replace_value(start, end, &Value::Table(outer));
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+
+    // ## Helpers ##
+
+    fn entries(&self) -> Vec<Entry> {
+        let mut entries = Vec::new();
+        let mut table = String::new();
+        let mut index = 0;
+
+        while index < self.lines.len() {
+            let line = &self.lines[index];
+            if is_header(line) {
+                table = header_name(line);
+                entries.push(Entry { table: table.clone(), key: None, start: index, end: index });
+            } else if let Some(key) = line_key(line) {
+                let end = value_end(&self.lines, index);
+                entries.push(Entry { table: table.clone(), key: Some(String::from(key)), start: index, end: end });
+                index = end;
+            }
+
+            index += 1;
+        }
+
+        entries
+    }
+
+    fn has_table(&self, table: &str) -> bool {
+        self.entries().iter().any(|e| e.key.is_none() && e.table == table)
+    }
+
+    fn find_key(&self, table: &str, key: &str) -> Option<(usize, usize)> {
+        self.entries().iter()
+            .find(|e| e.table == table && e.key.as_ref().map(|k| &k[..]) == Some(key))
+            .map(|e| (e.start, e.end))
+    }
+
+    // Right after the last key of a table, or after its header if it has none. Top level keys
+    // without any siblings go before the first table.
+    fn insert_point(&self, table: &str) -> usize {
+        let entries = self.entries();
+        let last = entries.iter()
+            .filter(|e| e.table == table && (!table.is_empty() || e.key.is_some()))
+            .map(|e| e.end + 1)
+            .max();
+
+        match last {
+            Some(index) => index,
+            None => entries.iter()
+                .find(|e| e.key.is_none())
+                .map(|e| e.start)
+                .unwrap_or(self.lines.len())
+        }
+    }
+
+    // New tables go after their siblings so `[dependencies.*]` sections stay together
+    fn append_table(&mut self, table: &str, line: String) {
+        let family = table.split('.').next().unwrap_or(table);
+        let family_prefix = format!("{}.", family);
+        let index = self.entries().iter()
+            .filter(|e| !e.table.is_empty() && (e.table == family || e.table.starts_with(&family_prefix)))
+            .map(|e| e.end + 1)
+            .max()
+            .unwrap_or(self.lines.len());
+
+        let mut section = vec![String::new(), format!("[{}]", table), line];
+        if index < self.lines.len() && !self.lines[index].trim().is_empty() {
+            section.push(String::new());
+        }
+
+        for (offset, line) in section.into_iter().enumerate() {
+            self.lines.insert(index + offset, line);
+        }
+    }
+
+    // Removes the table's section along with all of its sub tables
+    fn remove_table(&mut self, table: &str) {
+        let prefix = format!("{}.", table);
+
+        loop {
+            let entries = self.entries();
+            let header = entries.iter()
+                .position(|e| e.key.is_none() && (e.table == table || e.table.starts_with(&prefix)));
+
+            let header = match header {
+                Some(header) => header,
+                None => break
+            };
+
+            let start = entries[header].start;
+            let end = entries[header + 1..].iter()
+                .find(|e| e.key.is_none())
+                .map(|e| e.start)
+                .unwrap_or(self.lines.len());
+
+            for _ in start..end {
+                self.lines.remove(start);
+            }
+        }
+    }
+
+    fn replace_value(&mut self, start: usize, end: usize, value: &Value) {
+        let line = self.lines[start].clone();
+        let equals = line.find('=').expect("a key line always has an `=`");
+        let after = &line[equals + 1..];
+        let spacing = &after[..after.len() - after.trim_left().len()];
+
+        let mut new_line = format!("{}={}{}",
+            &line[..equals],
+            if spacing.is_empty() { " " } else { spacing },
+            format_value(value));
+
+        // Keep a trailing comment, only possible when the value was on a single line
+        if start == end {
+            if let Some(offset) = comment_start(after) {
+                let old_value = &after[..offset];
+                let gap = &old_value[old_value.trim_right().len()..];
+                new_line.push_str(if gap.is_empty() { " " } else { gap });
+                new_line.push_str(&after[offset..]);
+            }
+        }
+
+        self.lines[start] = new_line;
+        for _ in start + 1..end + 1 {
+            self.lines.remove(start + 1);
+        }
+    }
+}
+
+impl Display for ManifestEditor {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        try!(write!(f, "{}", self.lines.join("\n")));
+        if self.trailing_newline {
+            try!(write!(f, "\n"));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    table: String,
+    key: Option<String>,
+    start: usize,
+    end: usize
+}
+
+
+/// Writes `contents` to a hidden file next to `path` and renames it over `path`, readers see
+/// either the old file or the whole new one, never a half written one.
+pub fn replace_file(path: &Path, contents: &str) -> Result<(), ZeusProjectError> {
+    let temp_path = temp_path(path);
+    let written = File::create(&temp_path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()).and_then(|_| file.sync_all()))
+        .and_then(|_| fs::rename(&temp_path, path));
+
+    match written {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            let _ = fs::remove_file(&temp_path);
+            Err(ZeusProjectError::io(path, e))
+        }
+    }
+}
+
+// Renaming only works within a filesystem, so the temporary file goes next to the real one
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(".");
+    name.push(path.file_name().unwrap_or(path.as_os_str()));
+    name.push(".zeus-saving");
+    path.with_file_name(name)
+}
+
+
+// ### Value Helpers ###
+
+/// Formats a value the way it would be written on a single line, tables become inline tables.
+pub fn format_value(value: &Value) -> String {
+    match *value {
+        Value::Array(ref values) => {
+            let values: Vec<String> = values.iter().map(format_value).collect();
+            format!("[{}]", values.join(", "))
+        },
+        Value::Table(ref table) => {
+            if table.is_empty() { return String::from("{}"); }
+
+            let values: Vec<String> = table.iter()
+                .map(|(k, v)| format!("{} = {}", format_key(k), format_value(v)))
+                .collect();
+            format!("{{ {} }}", values.join(", "))
+        },
+        ref value => value.to_string()
+    }
+}
+
+fn format_key(key: &str) -> String {
+    let bare = !key.is_empty() && key.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        String::from(key)
+    } else {
+        Value::String(String::from(key)).to_string()
+    }
+}
+
+fn split_key(key: &str) -> Result<Vec<&str>, ZeusProjectError> {
+    let parts: Vec<&str> = key.split('.').collect();
+    if parts.iter().any(|part| part.trim().is_empty()) {
+        return Err(ZeusProjectError::InvalidKey(String::from(key)));
+    }
+
+    Ok(parts)
+}
+
+fn insert_nested(table: &mut Table, path: &[&str], value: Value) -> bool {
+    if path.len() == 1 {
+        table.insert(String::from(path[0]), value);
+        return true;
+    }
+
+    match *table.entry(String::from(path[0])).or_insert(Value::Table(Table::new())) {
+        Value::Table(ref mut inner) => insert_nested(inner, &path[1..], value),
+        _ => false
+    }
+}
+
+fn remove_nested(table: &mut Table, path: &[&str]) -> bool {
+    if path.len() == 1 {
+        return table.remove(path[0]).is_some();
+    }
+
+    match table.get_mut(path[0]) {
+        Some(&mut Value::Table(ref mut inner)) => remove_nested(inner, &path[1..]),
+        _ => false
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use toml::Value;
+
+    use super::ManifestEditor;
+
+    fn string(value: &str) -> Value {
+        Value::String(String::from(value))
+    }
+
+    fn edited<F: FnOnce(&mut ManifestEditor)>(source: &str, edit: F) -> String {
+        let mut editor = ManifestEditor::new(source);
+        edit(&mut editor);
+        editor.to_string()
+    }
+
+    #[test]
+    fn set_keeps_a_trailing_comment() {
+        let result = edited("# The game\n[game]\nversion = \"0.1.0\"  # bump on release\n", |editor| {
+            editor.set("game.version", string("0.2.0")).unwrap();
+        });
+
+        assert_eq!(result, "# The game\n[game]\nversion = \"0.2.0\"  # bump on release\n");
+    }
+
+    #[test]
+    fn set_adds_a_key_after_the_last_one_of_its_table() {
+        let result = edited("[game]\nname = \"test\"\n\n[athena]\nversion = \"develop\"\n", |editor| {
+            editor.set("game.version", string("0.1.0")).unwrap();
+        });
+
+        assert_eq!(result, "[game]\nname = \"test\"\nversion = \"0.1.0\"\n\n[athena]\nversion = \"develop\"\n");
+    }
+
+    #[test]
+    fn remove_of_the_last_key_keeps_the_table() {
+        let result = edited("[game]\nname = \"test\"\n\n[athena]\nversion = \"develop\"\n", |editor| {
+            assert!(editor.remove("athena.version").unwrap());
+        });
+
+        assert_eq!(result, "[game]\nname = \"test\"\n\n[athena]\n");
+    }
+
+    #[test]
+    fn remove_of_a_missing_key_changes_nothing() {
+        let source = "[game]\nname = \"test\"\n";
+        let result = edited(source, |editor| {
+            assert!(!editor.remove("game.version").unwrap());
+        });
+
+        assert_eq!(result, source);
+    }
+
+    #[test]
+    fn set_appends_a_missing_table() {
+        let result = edited("[game]\nname = \"test\"\n", |editor| {
+            editor.set("athena.version", string("develop")).unwrap();
+        });
+
+        assert_eq!(result, "[game]\nname = \"test\"\n\n[athena]\nversion = \"develop\"\n");
+    }
+
+    #[test]
+    fn new_tables_go_after_their_siblings() {
+        let result = edited("[dependencies.physics]\ngit = \"a\"\n\n[game]\nname = \"test\"\n", |editor| {
+            editor.set("dependencies.audio.git", string("b")).unwrap();
+        });
+
+        assert_eq!(result, "[dependencies.physics]\ngit = \"a\"\n\n[dependencies.audio]\ngit = \"b\"\n\n[game]\nname = \"test\"\n");
+    }
+
+    #[test]
+    fn set_replaces_a_multi_line_array() {
+        let source = "[game]\nauthors = [\n    \"Jane Doe\",\n    \"John Doe\",\n]\nname = \"test\"\n";
+        let result = edited(source, |editor| {
+            editor.set("game.authors", Value::Array(vec![string("Jane Doe")])).unwrap();
+        });
+
+        assert_eq!(result, "[game]\nauthors = [\"Jane Doe\"]\nname = \"test\"\n");
+    }
+
+    #[test]
+    fn keys_after_a_multi_line_array_are_found() {
+        let source = "[game]\nauthors = [\n    \"Jane Doe\",\n]\nname = \"test\"\n";
+        let result = edited(source, |editor| {
+            editor.set("game.name", string("renamed")).unwrap();
+            assert!(editor.remove("game.authors").unwrap());
+        });
+
+        assert_eq!(result, "[game]\nname = \"renamed\"\n");
+    }
+}
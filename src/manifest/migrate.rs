@@ -0,0 +1,193 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::ffi::OsString;
+use std::fs;
+use std::fs::PathExt;
+use std::path::{Path, PathBuf};
+use toml::Value;
+
+use manifest::ManifestEditor;
+use project::ZeusProjectError;
+
+/// The manifest schema this zeus writes and understands. Manifests without a
+/// `manifest_version` predate versioning and are schema 1.
+pub static CURRENT_VERSION: i64 = 2;
+
+// ### Migrations ###
+
+/// A single step rewriting a manifest from schema `from` to `from + 1`.
+pub struct Migration {
+    pub from: i64,
+    pub description: &'static str,
+    apply: fn(&mut ManifestEditor) -> Result<(), ZeusProjectError>
+}
+
+/// Every migration step, in order. A schema change only needs a new entry here.
+pub static MIGRATIONS: &'static [Migration] = &[
+    Migration {
+        from: 1,
+        description: "move `game.athena_version` to `athena.version`",
+        apply: move_athena_version
+    }
+];
+
+fn move_athena_version(editor: &mut ManifestEditor) -> Result<(), ZeusProjectError> {
+    if let Some(version) = editor.get("game.athena_version") {
+        try!(editor.remove("game.athena_version"));
+        try!(editor.set("athena.version", version));
+    }
+
+    Ok(())
+}
+
+
+// ### Migrating ###
+
+/// What migrating a manifest did, or would do on a dry run.
+#[derive(Debug, Clone)]
+pub struct MigrationOutcome {
+    pub from: i64,
+    pub to: i64,
+    pub steps: Vec<&'static str>,
+    pub original: String,
+    pub migrated: String,
+    pub backup: Option<PathBuf>
+}
+
+impl MigrationOutcome {
+    pub fn is_up_to_date(&self) -> bool { self.steps.is_empty() }
+}
+
+pub fn manifest_version(editor: &ManifestEditor) -> Result<i64, ZeusProjectError> {
+    match editor.get("manifest_version") {
+        None => Ok(1),
+        Some(Value::Integer(version)) => Ok(version),
+        Some(_) => Err(ZeusProjectError::InvalidKey(String::from("manifest_version")))
+    }
+}
+
+pub fn migrate_str(source: &str) -> Result<MigrationOutcome, ZeusProjectError> {
+    let mut editor = ManifestEditor::new(source);
+    let from = try!(manifest_version(&editor));
+    if from > CURRENT_VERSION {
+        return Err(ZeusProjectError::UnsupportedManifest(from));
+    }
+
+    let mut steps = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| m.from >= from) {
+        try!((migration.apply)(&mut editor));
+        steps.push(migration.description);
+    }
+
+    if from != CURRENT_VERSION {
+        try!(editor.set("manifest_version", Value::Integer(CURRENT_VERSION)));
+    }
+
+    Ok(MigrationOutcome {
+        from: from,
+        to: CURRENT_VERSION,
+        steps: steps,
+        original: String::from(source),
+        migrated: editor.to_string(),
+        backup: None
+    })
+}
+
+/// Migrates a manifest on disk, keeping the original next to it. Nothing is written on a dry run.
+pub fn migrate_file(path: &Path, dry_run: bool) -> Result<MigrationOutcome, ZeusProjectError> {
+    let editor = try!(ManifestEditor::open(path));
+    let mut outcome = try!(migrate_str(&editor.to_string()));
+    if dry_run || outcome.is_up_to_date() {
+        return Ok(outcome);
+    }
+
+    let backup = backup_path(path, outcome.from);
+    try!(fs::copy(path, &backup).map_err(|e| ZeusProjectError::io(&backup, e)));
+    try!(ManifestEditor::new(&outcome.migrated).save(path));
+
+    outcome.backup = Some(backup);
+    Ok(outcome)
+}
+
+// Earlier backups are never overwritten, every migration gets its own number
+fn backup_path(path: &Path, version: i64) -> PathBuf {
+    let mut number = 1;
+    loop {
+        let mut name = path.file_name().map(OsString::from).unwrap_or(OsString::from("Zeus.toml"));
+        if number == 1 {
+            name.push(format!(".v{}.bak", version));
+        } else {
+            name.push(format!(".v{}.{}.bak", version, number));
+        }
+
+        let backup = path.with_file_name(name);
+        if !backup.exists() {
+            return backup;
+        }
+        number += 1;
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use toml::{Parser, Value};
+
+    use project::ZeusProjectError;
+    use super::{migrate_str, CURRENT_VERSION};
+
+    fn parse(source: &str) -> Value {
+        Value::Table(Parser::new(source).parse().expect("migrated manifests parse"))
+    }
+
+    #[test]
+    fn version_1_moves_the_athena_version() {
+        let outcome = migrate_str("# My game\n[game]\nname = \"test\"\nathena_version = \"develop\"\n").unwrap();
+        let migrated = parse(&outcome.migrated);
+
+        assert_eq!((outcome.from, outcome.to), (1, CURRENT_VERSION));
+        assert_eq!(outcome.steps.len(), 1);
+        assert_eq!(migrated.lookup("athena.version").and_then(|v| v.as_str()), Some("develop"));
+        assert!(migrated.lookup("game.athena_version").is_none());
+        assert_eq!(migrated.lookup("manifest_version").and_then(|v| v.as_integer()), Some(CURRENT_VERSION));
+        assert!(outcome.migrated.starts_with("# My game\n"));
+    }
+
+    #[test]
+    fn version_1_without_an_athena_version_only_gets_a_version() {
+        let outcome = migrate_str("[game]\nname = \"test\"\n").unwrap();
+        let migrated = parse(&outcome.migrated);
+
+        assert!(migrated.lookup("athena").is_none());
+        assert_eq!(migrated.lookup("manifest_version").and_then(|v| v.as_integer()), Some(CURRENT_VERSION));
+    }
+
+    #[test]
+    fn current_manifests_are_left_alone() {
+        let source = "manifest_version = 2\n\n[game]\nname = \"test\"\n\n[athena]\nversion = \"develop\"\n";
+        let outcome = migrate_str(source).unwrap();
+
+        assert!(outcome.is_up_to_date());
+        assert_eq!(outcome.migrated, source);
+    }
+
+    #[test]
+    fn newer_manifests_are_refused() {
+        match migrate_str("manifest_version = 3\n\n[game]\nname = \"test\"\n") {
+            Err(ZeusProjectError::UnsupportedManifest(3)) => {},
+            other => panic!("expected UnsupportedManifest(3), got {:?}", other.map(|outcome| outcome.migrated))
+        }
+    }
+}
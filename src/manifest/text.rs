@@ -0,0 +1,136 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Line level helpers for looking at manifests the way they are written rather than the way they
+//! parse, the toml parser throws away positions, comments and ordering.
+
+/// The dotted name of a `[table]` header line, with whitespace and quotes around parts removed.
+pub fn header_name(line: &str) -> String {
+    let inner = line.trim().trim_left_matches('[');
+    let inner = match inner.find(']') {
+        Some(end) => &inner[..end],
+        None => inner
+    };
+
+    inner.split('.')
+        .map(|part| part.trim().trim_matches('"'))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+pub fn is_header(line: &str) -> bool {
+    line.trim_left().starts_with('[')
+}
+
+/// The key assigned on a `key = value` line, if it is one.
+pub fn line_key(line: &str) -> Option<&str> {
+    let line = line.trim_left();
+    if line.starts_with('#') || line.starts_with('[') { return None; }
+
+    match line.find('=') {
+        Some(end) => Some(line[..end].trim().trim_matches('"')),
+        None => None
+    }
+}
+
+/// Finds the last line of a value starting on `lines[start]`, arrays and multi-line strings
+/// can span several lines.
+pub fn value_end(lines: &[String], start: usize) -> usize {
+    let mut depth = 0i32;
+    let mut state = Quote::None;
+
+    for index in start..lines.len() {
+        let line = &lines[index];
+        let mut chars = line.char_indices().peekable();
+
+        // Only start looking after the `=` on the first line
+        if index == start {
+            while let Some((_, c)) = chars.next() {
+                if c == '=' { break; }
+            }
+        }
+
+        while let Some((offset, c)) = chars.next() {
+            match state {
+                Quote::None => match c {
+                    '#' => break,
+                    '[' | '{' => depth += 1,
+                    ']' | '}' => depth -= 1,
+                    '"' | '\'' => {
+                        let triple = line[offset..].starts_with(if c == '"' { "\"\"\"" } else { "'''" });
+                        if triple {
+                            chars.next();
+                            chars.next();
+                            state = Quote::Multi(c);
+                        } else {
+                            state = Quote::Single(c);
+                        }
+                    },
+                    _ => {}
+                },
+                Quote::Single(quote) => {
+                    if c == '\\' && quote == '"' { chars.next(); }
+                    else if c == quote { state = Quote::None; }
+                },
+                Quote::Multi(quote) => {
+                    if c == '\\' && quote == '"' { chars.next(); }
+                    else if c == quote && line[offset..].starts_with(if quote == '"' { "\"\"\"" } else { "'''" }) {
+                        chars.next();
+                        chars.next();
+                        state = Quote::None;
+                    }
+                }
+            }
+        }
+
+        // Basic strings can't continue on the next line
+        if let Quote::Single(_) = state { state = Quote::None; }
+
+        if depth <= 0 && state == Quote::None {
+            return index;
+        }
+    }
+
+    lines.len() - 1
+}
+
+/// The byte offset of a trailing `# comment` on a single line value.
+pub fn comment_start(line: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut escaped = false;
+
+    for (offset, c) in line.char_indices() {
+        match quote {
+            Some(q) => {
+                if escaped { escaped = false; }
+                else if c == '\\' && q == '"' { escaped = true; }
+                else if c == q { quote = None; }
+            },
+            None => match c {
+                '"' | '\'' => quote = Some(c),
+                '#' => return Some(offset),
+                _ => {}
+            }
+        }
+    }
+
+    None
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Quote {
+    None,
+    Single(char),
+    Multi(char)
+}
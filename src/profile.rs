@@ -0,0 +1,276 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Build profiles, `[profile.<name>]` in Zeus.toml. `dev` and `release` always exist and may be
+//! tweaked, other profiles inherit from one of them (or from each other) and override what they
+//! need:
+//!
+//! ```toml
+//! [profile.shipping]
+//! inherits = "release"
+//! asset-compression = 9
+//! log-level = "warn"
+//! ```
+//!
+//! Custom profiles inherit from `dev` unless they say otherwise. Every key overrides the one it
+//! inherits, `features` included.
+
+use std::ascii::AsciiExt;
+use std::path::PathBuf;
+use toml::{Table, Value};
+
+use project::ZeusProjectError;
+
+/// Keys a `[profile.<name>]` table may use.
+pub static PROFILE_KEYS: &'static [&'static str] =
+    &["inherits", "opt-level", "debug", "asset-compression", "log-level", "features"];
+
+/// Levels `log-level` may be set to, from quietest to loudest.
+pub static LOG_LEVELS: &'static [&'static str] = &["off", "error", "warn", "info", "debug", "trace"];
+
+/// The profile builds use when none is picked.
+pub static DEFAULT_PROFILE: &'static str = "dev";
+
+// Cargo's own profiles, everything inherits from one of them in the end
+static BASE_PROFILES: &'static [&'static str] = &["dev", "release"];
+
+/// Everything a build needs to know about its profile, inheritance already applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Profile {
+    pub name: String,
+    /// The cargo profile it compiles with, `dev` or `release`.
+    pub base: &'static str,
+    /// 0 to 3, as rustc understands it.
+    pub opt_level: i64,
+    pub debug: bool,
+    /// 0 for none to 9 for the smallest assets.
+    pub asset_compression: i64,
+    /// One of `LOG_LEVELS`.
+    pub log_level: String,
+    /// Features from `[features]` to turn on, or of a dependency when written like `athena/editor`.
+    pub features: Vec<String>
+}
+
+impl Profile {
+    fn dev() -> Profile {
+        Profile {
+            name: String::from("dev"),
+            base: "dev",
+            opt_level: 0,
+            debug: true,
+            asset_compression: 0,
+            log_level: String::from("debug"),
+            features: Vec::new()
+        }
+    }
+
+    fn release() -> Profile {
+        Profile {
+            name: String::from("release"),
+            base: "release",
+            opt_level: 3,
+            debug: false,
+            asset_compression: 6,
+            log_level: String::from("info"),
+            features: Vec::new()
+        }
+    }
+
+    /// Looks a profile up in the manifest's `[profile]` table, following what it inherits.
+    pub fn resolve(manifest: &Value, name: &str) -> Result<Profile, ZeusProjectError> {
+        let profiles = match manifest.lookup("profile") {
+            Some(&Value::Table(ref profiles)) => Some(profiles),
+            _ => None
+        };
+
+        Profile::resolve_in(profiles, name).map_err(|reason| ZeusProjectError::InvalidProfile {
+            name: String::from(name),
+            reason: reason
+        })
+    }
+
+    /// Same as `resolve`, but on the `[profile]` table itself.
+    pub fn resolve_in(profiles: Option<&Table>, name: &str) -> Result<Profile, String> {
+        // Walk up to the base profile first, the tables apply from there down
+        let mut chain: Vec<(&str, Option<&Table>)> = Vec::new();
+        let mut current = name;
+        loop {
+            try!(check_name(current));
+            if chain.iter().any(|&(seen, _)| seen == current) {
+                let names: Vec<&str> = chain.iter().map(|&(seen, _)| seen).collect();
+                return Err(format!("inherits from itself through {} -> {}", names.join(" -> "), current));
+            }
+
+            let table = match profiles.and_then(|profiles| profiles.get(current)) {
+                Some(&Value::Table(ref table)) => Some(table),
+                Some(_) => return Err(format!("`profile.{}` must be a table", current)),
+                None if BASE_PROFILES.contains(&current) => None,
+                None if current == name => return Err(format!("there is no `[profile.{}]`", current)),
+                None => return Err(format!("inherits from `{}`, which doesn't exist", current))
+            };
+            chain.push((current, table));
+
+            if BASE_PROFILES.contains(&current) {
+                if table.map_or(false, |table| table.contains_key("inherits")) {
+                    return Err(format!("`{}` is a base profile, it can't inherit", current));
+                }
+                break;
+            }
+
+            current = match table.and_then(|table| table.get("inherits")) {
+                Some(&Value::String(ref parent)) => &parent[..],
+                Some(_) => return Err(format!("`inherits` of `{}` must be a string", current)),
+                None => DEFAULT_PROFILE
+            };
+        }
+
+        let mut profile = if current == "release" { Profile::release() } else { Profile::dev() };
+        for &(_, table) in chain.iter().rev() {
+            if let Some(table) = table {
+                try!(profile.apply(table));
+            }
+        }
+
+        profile.name = String::from(name);
+        Ok(profile)
+    }
+
+    // Overrides whatever the table sets
+    fn apply(&mut self, table: &Table) -> Result<(), String> {
+        match table.get("opt-level") {
+            Some(&Value::Integer(level)) if level >= 0 && level <= 3 => self.opt_level = level,
+            Some(_) => return Err(String::from("`opt-level` must be a number from 0 to 3")),
+            None => {}
+        }
+
+        match table.get("debug") {
+            Some(&Value::Boolean(debug)) => self.debug = debug,
+            Some(_) => return Err(String::from("`debug` must be `true` or `false`")),
+            None => {}
+        }
+
+        match table.get("asset-compression") {
+            Some(&Value::Integer(level)) if level >= 0 && level <= 9 => self.asset_compression = level,
+            Some(_) => return Err(String::from("`asset-compression` must be a number from 0 to 9")),
+            None => {}
+        }
+
+        match table.get("log-level") {
+            Some(&Value::String(ref level)) if LOG_LEVELS.contains(&&level[..]) => self.log_level = level.clone(),
+            Some(_) => return Err(format!("`log-level` must be one of {}", LOG_LEVELS.join(", "))),
+            None => {}
+        }
+
+        match table.get("features") {
+            Some(&Value::Array(ref features)) => {
+                let names: Vec<String> = features.iter().filter_map(|f| f.as_str()).map(String::from).collect();
+                if names.len() != features.len() || names.iter().any(|name| name.trim().is_empty()) {
+                    return Err(String::from("`features` must be a list of feature names"));
+                }
+                self.features = names;
+            },
+            Some(_) => return Err(String::from("`features` must be a list of feature names")),
+            None => {}
+        }
+
+        Ok(())
+    }
+
+    pub fn is_release(&self) -> bool {
+        self.base == "release"
+    }
+
+    /// Where cargo puts what it builds, relative to the project. Custom profiles get their own
+    /// directory, cargo doesn't know they differ from the base profile and would mix them up.
+    pub fn target_dir(&self) -> PathBuf {
+        if BASE_PROFILES.contains(&&self.name[..]) {
+            PathBuf::from("target")
+        } else {
+            PathBuf::from("target").join(&self.name)
+        }
+    }
+
+    /// The profile's name, log level and asset compression.
+    pub fn vars(&self) -> Vec<(String, String)> {
+        vec![
+            (String::from("ATHENA_PROFILE"), self.name.clone()),
+            (String::from("ATHENA_LOG_LEVEL"), self.log_level.clone()),
+            (String::from("ATHENA_ASSET_COMPRESSION"), self.asset_compression.to_string())
+        ]
+    }
+}
+
+/// Every profile a manifest can build with, the base ones first.
+pub fn names(manifest: &Value) -> Vec<String> {
+    let mut names: Vec<String> = BASE_PROFILES.iter().map(|name| String::from(*name)).collect();
+    if let Some(&Value::Table(ref profiles)) = manifest.lookup("profile") {
+        names.extend(profiles.keys().filter(|name| !BASE_PROFILES.contains(&&name[..])).cloned());
+    }
+    names
+}
+
+// Names end up in the build's target directory, so they stick to what can't leave it
+fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii() && (c.is_alphanumeric() || c == '_' || c == '-')) {
+        return Err(format!("`{}` isn't a valid profile name, names may only use letters, digits, `_` and `-`", name));
+    }
+
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use toml::{Parser, Table, Value};
+
+    use super::Profile;
+
+    fn profiles(source: &str) -> Table {
+        match Parser::new(source).parse().unwrap().remove("profile") {
+            Some(Value::Table(profiles)) => profiles,
+            other => panic!("expected a [profile] table, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn overrides_apply_over_what_is_inherited() {
+        let profiles = profiles("[profile.release]\nopt-level = 2\n\n\
+                                 [profile.shipping]\ninherits = \"release\"\nasset-compression = 9\nlog-level = \"warn\"\n");
+        let profile = Profile::resolve_in(Some(&profiles), "shipping").unwrap();
+
+        assert_eq!(profile.name, "shipping");
+        assert_eq!(profile.base, "release");
+        assert_eq!(profile.opt_level, 2);
+        assert_eq!(profile.debug, false);
+        assert_eq!(profile.asset_compression, 9);
+        assert_eq!(profile.log_level, "warn");
+    }
+
+    #[test]
+    fn custom_profiles_inherit_from_dev() {
+        let profiles = profiles("[profile.profiling]\nopt-level = 1\n");
+        let profile = Profile::resolve_in(Some(&profiles), "profiling").unwrap();
+
+        assert_eq!(profile.base, "dev");
+        assert_eq!(profile.opt_level, 1);
+        assert_eq!(profile.debug, true);
+    }
+
+    #[test]
+    fn inheritance_cycles_are_refused() {
+        let profiles = profiles("[profile.a]\ninherits = \"b\"\n\n[profile.b]\ninherits = \"a\"\n");
+
+        assert_eq!(Profile::resolve_in(Some(&profiles), "a"), Err(String::from("inherits from itself through a -> b -> a")));
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Progress of long running operations like fetching Athena or building the game, for
+//! frontends to show however they like.
+
+use std::fmt::Debug;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Phase {
+    Resolve,
+    Fetch,
+    Verify,
+    Build
+}
+
+/// What `current` and `total` count.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Unit {
+    Steps,
+    Objects,
+    Bytes
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    pub phase: Phase,
+    /// What the phase is working on, like the package being fetched.
+    pub task: String,
+    /// What's happening right now, like git's `Receiving objects`.
+    pub detail: String,
+    pub current: u64,
+    /// None when there's no telling how much is left.
+    pub total: Option<u64>,
+    pub unit: Unit
+}
+
+impl Progress {
+    pub fn new(phase: Phase, task: &str, detail: &str) -> Progress {
+        Progress {
+            phase: phase,
+            task: String::from(task),
+            detail: String::from(detail),
+            current: 0,
+            total: None,
+            unit: Unit::Steps
+        }
+    }
+
+    /// Step `current` out of `total`.
+    pub fn step(phase: Phase, task: &str, detail: &str, current: u64, total: u64) -> Progress {
+        Progress { current: current, total: Some(total), ..Progress::new(phase, task, detail) }
+    }
+
+    pub fn percentage(&self) -> Option<u8> {
+        match self.total {
+            Some(0) => Some(100),
+            Some(total) => Some((self.current.min(total) * 100 / total) as u8),
+            None => None
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.total.map_or(false, |total| self.current >= total)
+    }
+}
+
+/// Receives progress as it happens. Reports come from the thread doing the work, so keep them quick.
+pub trait ProgressSink: Debug {
+    fn report(&self, progress: &Progress);
+}
+
+/// Throws progress away, for when nobody's watching.
+#[derive(Debug)]
+pub struct NoProgress;
+
+impl ProgressSink for NoProgress {
+    fn report(&self, _progress: &Progress) {}
+}
+
+/// Files progress from code that doesn't know what it's working for, like a clone, under a
+/// phase and task.
+#[derive(Debug)]
+pub struct TaskProgress<'a> {
+    sink: &'a ProgressSink,
+    phase: Phase,
+    task: String
+}
+
+impl<'a> TaskProgress<'a> {
+    pub fn new(sink: &'a ProgressSink, phase: Phase, task: &str) -> TaskProgress<'a> {
+        TaskProgress { sink: sink, phase: phase, task: String::from(task) }
+    }
+}
+
+impl<'a> ProgressSink for TaskProgress<'a> {
+    fn report(&self, progress: &Progress) {
+        self.sink.report(&Progress {
+            phase: self.phase,
+            task: self.task.clone(),
+            ..progress.clone()
+        });
+    }
+}
@@ -13,28 +13,71 @@
 // limitations under the License.
 
 use std::error::Error;
+use std::ffi::{OsStr, OsString};
 use std::fs;
 use std::fs::{PathExt};
 use std::fmt;
 use std::fmt::{Display, Formatter};
-use std::io::{Write};
-use std::path::PathBuf;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use toml;
 
-use git;
+use auth;
+use build;
+use build::BuildOptions;
+use cargo;
+use checksum;
+use compat;
+use compat::Compatibility;
+use config::Config;
+use dependency;
+use dependency::{Dependency, FetchOptions, Source};
+use features;
+use lockfile;
+use manifest::ManifestEditor;
+use manifest::migrate;
+use profile::Profile;
+use progress::{NoProgress, Phase, Progress, ProgressSink, TaskProgress};
+use resolver;
+use registry;
+use registry::Registry;
+use resolver::{Resolve, SourceIndex};
+use target;
+use target::Target;
+use toolchain;
+use toolchain::Toolchain;
+use vcs;
+use vcs::VcsBackend;
 
 // ### File Templates ###
 
 static PROJ_TOML: &'static str =
-r#"[game]
+r#"manifest_version = 2
+
+[game]
 name = "{{game_name}}"
 version = "0.0.1"
 zeus_version = "develop"
-athena_version = "develop"
-authors = ["{{author_name}}"]"#;
+authors = ["{{author_name}}"]
+
+[athena]
+version = "develop"
+"#;
+
+static MAIN_RS: &'static str =
+r#"fn main() {
+    println!("Hello from {{game_name}}!");
+}
+"#;
 
 static GITIGNORE: &'static str =
-r#"/athena"#;
+r#"/athena
+/deps
+/target
+/.zeus/build
+/.zeus/config.toml
+"#;
 
 
 // ### Create Error ###
@@ -44,7 +87,32 @@ pub enum ZeusProjectError {
     AlreadyExists,
     NotAZeusProject,
     InvalidPath,
-    CorruptedFile(String)
+    CorruptedFile(PathBuf),
+    Io { path: PathBuf, source: io::Error },
+    CommandFailed { command: String, output: String },
+    InvalidManifest { path: PathBuf, errors: usize },
+    InvalidKey(String),
+    MissingKey(String),
+    IncompatibleZeus { required: String, running: String },
+    UnsupportedManifest(i64),
+    InvalidDependency { name: String, reason: String },
+    ResolutionFailed(String),
+    ChecksumMismatch { path: PathBuf, expected: String, actual: String },
+    AlreadyPublished { name: String, version: String },
+    UnsupportedRegistry(String),
+    ToolNotFound { tool: String, hint: String },
+    AuthenticationFailed { host: String, hint: String },
+    InvalidSetting { key: String, origin: String, reason: String },
+    Offline(String),
+    InvalidProfile { name: String, reason: String },
+    UnknownFeature { name: String, reason: String },
+    InvalidTarget { triple: String, reason: String }
+}
+
+impl ZeusProjectError {
+    pub fn io(path: &Path, source: io::Error) -> ZeusProjectError {
+        ZeusProjectError::Io { path: path.to_path_buf(), source: source }
+    }
 }
 
 impl Error for ZeusProjectError {
@@ -53,7 +121,33 @@ impl Error for ZeusProjectError {
             ZeusProjectError::AlreadyExists => "Already Exists",
             ZeusProjectError::NotAZeusProject => "Not a Zeus Project",
             ZeusProjectError::InvalidPath => "Not a Valid Path",
-            ZeusProjectError::CorruptedFile(_) => "File Corrupted"
+            ZeusProjectError::CorruptedFile(_) => "File Corrupted",
+            ZeusProjectError::Io { .. } => "I/O Error",
+            ZeusProjectError::CommandFailed { .. } => "Command Failed",
+            ZeusProjectError::InvalidManifest { .. } => "Invalid Manifest",
+            ZeusProjectError::InvalidKey(_) => "Invalid Key",
+            ZeusProjectError::MissingKey(_) => "Missing Key",
+            ZeusProjectError::IncompatibleZeus { .. } => "Incompatible Zeus Version",
+            ZeusProjectError::UnsupportedManifest(_) => "Unsupported Manifest",
+            ZeusProjectError::InvalidDependency { .. } => "Invalid Dependency",
+            ZeusProjectError::ResolutionFailed(_) => "Resolution Failed",
+            ZeusProjectError::ChecksumMismatch { .. } => "Checksum Mismatch",
+            ZeusProjectError::AlreadyPublished { .. } => "Already Published",
+            ZeusProjectError::UnsupportedRegistry(_) => "Unsupported Registry",
+            ZeusProjectError::ToolNotFound { .. } => "Tool Not Found",
+            ZeusProjectError::AuthenticationFailed { .. } => "Authentication Failed",
+            ZeusProjectError::InvalidSetting { .. } => "Invalid Setting",
+            ZeusProjectError::Offline(_) => "Offline",
+            ZeusProjectError::InvalidProfile { .. } => "Invalid Profile",
+            ZeusProjectError::UnknownFeature { .. } => "Unknown Feature",
+            ZeusProjectError::InvalidTarget { .. } => "Invalid Target"
+        }
+    }
+
+    fn cause(&self) -> Option<&Error> {
+        match *self {
+            ZeusProjectError::Io { ref source, .. } => Some(source),
+            _ => None
         }
     }
 }
@@ -64,7 +158,37 @@ impl Display for ZeusProjectError {
             ZeusProjectError::AlreadyExists => String::from("Destination path already exists and is not empty."),
             ZeusProjectError::NotAZeusProject => String::from("Destination path is not a Zeus project."),
             ZeusProjectError::InvalidPath => String::from("Destination path is not valid."),
-            ZeusProjectError::CorruptedFile(ref file) => format!("The file {} has been corrupted and could not be read.", file)
+            ZeusProjectError::CorruptedFile(ref file) => format!("The file {} has been corrupted and could not be read.", file.display()),
+            ZeusProjectError::Io { ref path, ref source } => format!("Could not access {}: {}", path.display(), source),
+            ZeusProjectError::CommandFailed { ref command, ref output } => format!("The command `{}` failed:\n{}", command, output),
+            ZeusProjectError::InvalidManifest { ref path, errors } => format!("The manifest {} has {} error(s).", path.display(), errors),
+            ZeusProjectError::InvalidKey(ref key) => format!("The key `{}` can't be set in this manifest.", key),
+            ZeusProjectError::MissingKey(ref key) => format!("The key `{}` is not set in this manifest.", key),
+            ZeusProjectError::IncompatibleZeus { ref required, ref running } =>
+                format!("This project requires zeus {} but this is zeus {}.\n{}", required, running, compat::upgrade_hint(required)),
+            ZeusProjectError::UnsupportedManifest(version) =>
+                format!("The manifest uses schema version {} but this zeus only knows up to {}, upgrade zeus to use it.",
+                    version, migrate::CURRENT_VERSION),
+            ZeusProjectError::InvalidDependency { ref name, ref reason } => format!("The dependency `{}` is invalid: {}.", name, reason),
+            ZeusProjectError::ResolutionFailed(ref explanation) => format!("Could not resolve the project's dependencies, {}", explanation),
+            ZeusProjectError::ChecksumMismatch { ref path, ref expected, ref actual } =>
+                format!("The checksum of {} is {}, expected {}. It may have been tampered with, run \
+                    `zeus verify --update` if the change is expected or delete it to fetch it again.",
+                    path.display(), actual, expected),
+            ZeusProjectError::AlreadyPublished { ref name, ref version } =>
+                format!("Version {} of `{}` is already in the registry, bump the version to publish again.", version, name),
+            ZeusProjectError::UnsupportedRegistry(ref registry) =>
+                format!("Can not publish to {}, HTTP registries are read only. Publish to a directory and serve \
+                    that over HTTP instead.", registry),
+            ZeusProjectError::ToolNotFound { ref tool, ref hint } => format!("Could not find {}. {}", tool, hint),
+            ZeusProjectError::AuthenticationFailed { ref host, ref hint } => format!("Could not authenticate with {}. {}", host, hint),
+            ZeusProjectError::InvalidSetting { ref key, ref origin, ref reason } =>
+                format!("The setting `{}` from {} {}.", key, origin, reason),
+            ZeusProjectError::Offline(ref what) =>
+                format!("Could not get {} while offline, unset `net.offline` and ZEUS_OFFLINE to go online.", what),
+            ZeusProjectError::InvalidProfile { ref name, ref reason } => format!("The profile `{}` is invalid: {}.", name, reason),
+            ZeusProjectError::UnknownFeature { ref name, ref reason } => format!("Can not turn on the feature `{}`, {}.", name, reason),
+            ZeusProjectError::InvalidTarget { ref triple, ref reason } => format!("The target `{}` is invalid: {}.", triple, reason)
         };
 
         return write!(f, "{}", message);
@@ -74,10 +198,20 @@ impl Display for ZeusProjectError {
 
 // ### Zeus Project ###
 
+/// Where cargo's crates go inside the vendor directory, hidden so it never takes the name of a
+/// dependency vendored next to it.
+static VENDORED_CRATES: &'static str = ".crates";
+
 #[derive(Debug)]
 pub struct ZeusProject {
     directory: PathBuf,
-    game_name: String
+    game_name: String,
+    manifest: toml::Value,
+    dependencies: Vec<Dependency>,
+    warnings: Vec<String>,
+    config: Config,
+    vcs: Rc<VcsBackend>,
+    progress: Rc<ProgressSink>
 }
 
 impl ZeusProject {
@@ -85,127 +219,1007 @@ impl ZeusProject {
 
     pub fn directory(&self) -> &PathBuf { &self.directory }
     pub fn game_name(&self) -> &str { &self.game_name }
+    pub fn manifest(&self) -> &toml::Value { &self.manifest }
+    pub fn dependencies(&self) -> &[Dependency] { &self.dependencies }
+    pub fn warnings(&self) -> &[String] { &self.warnings }
+    pub fn config(&self) -> &Config { &self.config }
+    pub fn vcs(&self) -> &Rc<VcsBackend> { &self.vcs }
+    pub fn progress(&self) -> &Rc<ProgressSink> { &self.progress }
+
+    /// Reports the progress of setting up and building the project to `progress` from now on.
+    pub fn set_progress(&mut self, progress: Rc<ProgressSink>) { self.progress = progress; }
 
 
     // ## Constructors ##
 
     pub fn create(target_dir: PathBuf) -> Result<ZeusProject, ZeusProjectError> {
+        ZeusProject::create_with_config(target_dir, try!(Config::load(None)))
+    }
+
+    /// Creates a project with settings layered by the caller, like command line flags.
+    pub fn create_with_config(target_dir: PathBuf, config: Config) -> Result<ZeusProject, ZeusProjectError> {
         // Sanity check the path
-        if target_dir.to_str().unwrap().is_empty() { return Err(ZeusProjectError::InvalidPath) }
+        if target_dir.as_os_str().is_empty() { return Err(ZeusProjectError::InvalidPath) }
 
         // Check if the directory already exists
         if target_dir.exists() {
             // It does, check if it's empty
-            if fs::read_dir(target_dir.clone()).unwrap().count() != 0 {
+            let entries = try!(fs::read_dir(&target_dir).map_err(|e| ZeusProjectError::io(&target_dir, e)));
+            if entries.count() != 0 {
                 // It isn't empty, we can't create a project here
                 return Err(ZeusProjectError::AlreadyExists);
             }
-        } else {
-            // It doesn't create it
-            fs::create_dir_all(target_dir.clone()).unwrap();
         }
 
-        // Create the actual project
-        let project = ZeusProject {
-            directory: target_dir,
-            game_name: String::from("My Game")
+        // Stage the project next to the target so a failure never leaves a half-written project behind
+        let name = match target_dir.file_name() {
+            Some(name) => name.to_os_string(),
+            None => return Err(ZeusProjectError::InvalidPath)
+        };
+        let vcs = try!(vcs::configured_backend(&config));
+        let created = try!(create_parents(&target_dir));
+        let staging_dir = match create_staging_dir(&target_dir, &name) {
+            Ok(dir) => dir,
+            Err(e) => {
+                remove_created(&created);
+                return Err(e);
+            }
+        };
+
+        // An existing target is empty, but it's the user's and has to be there again if anything fails
+        let existing = fs::metadata(&target_dir).ok().map(|metadata| metadata.permissions());
+
+        let staged = ZeusProject {
+            directory: staging_dir.clone(),
+            game_name: String::from("My Game"),
+            manifest: toml::Value::Table(toml::Table::new()),
+            dependencies: Vec::new(),
+            warnings: Vec::new(),
+            config: config,
+            vcs: vcs,
+            progress: Rc::new(NoProgress)
         };
 
-        // Generate the sample project file
-        // TODO: Use some templating library
-        let proj_toml = str::replace(PROJ_TOML, "{{game_name}}", "My Game");
-        let proj_toml = str::replace(&proj_toml, "{{author_name}}", "Jane Doe");
+        // Open the actual project, one that doesn't open is as good as one that was never created
+        let result = staged.write_template()
+            .and_then(|_| move_into_place(&staging_dir, &target_dir, existing.as_ref()))
+            .and_then(|_| ZeusProject::open_with(target_dir.clone(), staged.config.clone(), staged.vcs.clone())
+                .map_err(|e| {
+                    let _ = restore_target(&target_dir, existing.as_ref());
+                    e
+                }));
 
-        // Create basic
-        project.create_file("Zeus.toml", &proj_toml);
-        project.create_file(".gitignore", GITIGNORE);
+        if result.is_err() {
+            // Clean up whatever we staged, the original error is the one worth reporting
+            let _ = fs::remove_dir_all(&staging_dir);
+            remove_created(&created);
+        }
 
-        Ok(project)
+        result
     }
 
     pub fn open(target_dir: PathBuf) -> Result<ZeusProject, ZeusProjectError> {
+        let config = try!(Config::load(Some(&target_dir)));
+        ZeusProject::open_with_config(target_dir, config)
+    }
+
+    /// Opens a project with settings layered by the caller, like command line flags.
+    pub fn open_with_config(target_dir: PathBuf, config: Config) -> Result<ZeusProject, ZeusProjectError> {
+        let vcs = try!(vcs::configured_backend(&config));
+        ZeusProject::open_with(target_dir, config, vcs)
+    }
+
+    /// Opens a project that talks to repositories through the given backend instead of the default.
+    pub fn open_with_vcs(target_dir: PathBuf, vcs: Rc<VcsBackend>) -> Result<ZeusProject, ZeusProjectError> {
+        let config = try!(Config::load(Some(&target_dir)));
+        ZeusProject::open_with(target_dir, config, vcs)
+    }
+
+    fn open_with(target_dir: PathBuf, config: Config, vcs: Rc<VcsBackend>) -> Result<ZeusProject, ZeusProjectError> {
         let mut project = ZeusProject {
             directory: target_dir.clone(),
-            game_name: String::new()
+            game_name: String::new(),
+            manifest: toml::Value::Table(toml::Table::new()),
+            dependencies: Vec::new(),
+            warnings: Vec::new(),
+            config: config,
+            vcs: vcs,
+            progress: Rc::new(NoProgress)
         };
 
         // Sanity check the path
-        if !project.file_exists("Zeus.toml") { return Err(ZeusProjectError::NotAZeusProject); }
+        if !try!(project.file_exists("Zeus.toml")) { return Err(ZeusProjectError::NotAZeusProject); }
 
         // Parse in the toml file
         let value: toml::Value = try!(project.parse_file("Zeus.toml"));
-        project.game_name = String::from(value.lookup("game.name").unwrap().as_str().unwrap());
+        project.game_name = match value.lookup("game.name").and_then(|v| v.as_str()) {
+            Some(name) => String::from(name),
+            None => return Err(ZeusProjectError::CorruptedFile(project.get_file_path("Zeus.toml")))
+        };
+
+        // Older manifests still open, newer ones could mean anything
+        let manifest_version = match value.lookup("manifest_version") {
+            Some(&toml::Value::Integer(version)) => version,
+            Some(_) => return Err(ZeusProjectError::CorruptedFile(project.get_file_path("Zeus.toml"))),
+            None => 1
+        };
+        if manifest_version > migrate::CURRENT_VERSION {
+            return Err(ZeusProjectError::UnsupportedManifest(manifest_version));
+        } else if manifest_version < migrate::CURRENT_VERSION {
+            project.warnings.push(format!(
+                "Zeus.toml uses schema version {}, run `zeus migrate` to update it to version {}.",
+                manifest_version, migrate::CURRENT_VERSION));
+        }
+
+        // Make sure we're not about to misbuild a project made for another zeus
+        if let Some(requirement) = value.lookup("game.zeus_version").and_then(|v| v.as_str()) {
+            match compat::check_zeus_version(requirement) {
+                Compatibility::Compatible => {},
+                Compatibility::OtherChannel(channel) => project.warnings.push(format!(
+                    "This project targets the zeus `{}` channel but this is zeus {} from `{}`. {}",
+                    channel, compat::ZEUS_VERSION, compat::ZEUS_CHANNEL, compat::upgrade_hint(&channel))),
+                Compatibility::Incompatible(required) => return Err(ZeusProjectError::IncompatibleZeus {
+                    required: required,
+                    running: String::from(compat::ZEUS_VERSION)
+                })
+            }
+        }
+
+        // Broken toolchain pins stop builds, say why as soon as the project opens
+        if let Err(reason) = Toolchain::from_manifest(&value) {
+            project.warnings.push(format!("[toolchain] is invalid, nothing builds until it's fixed: {}.", reason));
+        }
+
+        // Read in what the game depends on
+        project.dependencies = match value.lookup("dependencies") {
+            Some(&toml::Value::Table(ref table)) => try!(dependency::parse_dependencies(table)),
+            Some(_) => return Err(ZeusProjectError::CorruptedFile(project.get_file_path("Zeus.toml"))),
+            None => Vec::new()
+        };
 
+        project.manifest = value;
         Ok(project)
     }
 
 
     // ## Helpers Functions ##
 
-    pub fn build_editor(&self) {
-        self.redownload_athena();
+    fn write_template(&self) -> Result<(), ZeusProjectError> {
+        // Generate the sample project file
+        // TODO: Use some templating library
+        let proj_toml = str::replace(PROJ_TOML, "{{game_name}}", &self.game_name);
+        let proj_toml = str::replace(&proj_toml, "{{author_name}}", self.config.author().unwrap_or("Jane Doe"));
+
+        // Create basic
+        try!(self.create_file("Zeus.toml", &proj_toml));
+        try!(self.create_file(".gitignore", GITIGNORE));
+
+        let src_dir = self.get_file_path("src");
+        try!(fs::create_dir_all(&src_dir).map_err(|e| ZeusProjectError::io(&src_dir, e)));
+        try!(self.create_file("src/main.rs", &str::replace(MAIN_RS, "{{game_name}}", &self.game_name)));
+
+        Ok(())
+    }
+
+    pub fn build_editor(&self) -> Result<(), ZeusProjectError> {
+        // The editor comes with Athena, setting the project up is all it takes for now
+        self.setup().map(|_| ())
     }
 
-    fn redownload_athena(&self) {
-        let mut athena_dir = self.directory.clone();
-        athena_dir.push("athena");
+    /// Resolves, fetches and locks everything the game needs and generates its cargo project.
+    pub fn setup(&self) -> Result<Resolve, ZeusProjectError> {
+        // Installing a toolchain takes the network, offline it has to be there already
+        if let Some(toolchain) = try!(self.toolchain()) {
+            self.progress.report(&Progress::new(Phase::Fetch, "toolchain", &format!("Checking {}", toolchain.name())));
+            try!(toolchain::ensure(&toolchain, !self.config.offline()));
+        }
+
+        let resolve = try!(self.resolve());
+        let resolve = try!(self.fetch(&resolve));
+
+        // Sources that don't match what was locked must never get built. They're left where they
+        // are, they may hold local edits and offline there'd be no getting them back
+        let resolve = try!(self.verify(&resolve, false));
+
+        try!(lockfile::save(&self.get_file_path(lockfile::LOCKFILE), &resolve));
+        try!(self.write_build_manifest(&BuildOptions::default()));
+
+        Ok(resolve)
+    }
+
+    /// Resolves the project and writes Zeus.lock, without fetching anything.
+    pub fn lock(&self) -> Result<Resolve, ZeusProjectError> {
+        let resolve = try!(self.resolve());
+        try!(lockfile::save(&self.get_file_path(lockfile::LOCKFILE), &resolve));
+
+        Ok(resolve)
+    }
 
-        // Delete the old folder if it exists
-        if athena_dir.exists() {
-            // Set readonly on all files and directories to false
-            for file in fs::walk_dir(&athena_dir).unwrap() {
-                let path = file.unwrap().path();
-                let mut permissions = fs::metadata(&path).unwrap().permissions();
-                permissions.set_readonly(false);
-                fs::set_permissions(&path, permissions).unwrap();
+    /// Edits Zeus.toml and re-locks the project with the result. If the edited manifest doesn't
+    /// open or resolve, the original is put back and nothing changes.
+    pub fn update_manifest<F>(&self, edit: F) -> Result<ZeusProject, ZeusProjectError>
+        where F: FnOnce(&mut ManifestEditor) -> Result<(), ZeusProjectError>
+    {
+        let manifest_path = self.get_file_path("Zeus.toml");
+        let original = try!(ManifestEditor::open(&manifest_path));
+
+        let mut edited = original.clone();
+        try!(edit(&mut edited));
+        try!(edited.save(&manifest_path));
+
+        let updated = ZeusProject::open_with(self.directory.clone(), self.config.clone(), self.vcs.clone()).and_then(|mut project| {
+            project.set_progress(self.progress.clone());
+            try!(project.lock());
+            Ok(project)
+        });
+
+        if updated.is_err() {
+            try!(original.save(&manifest_path));
+        }
+
+        updated
+    }
+
+    /// Makes sure a dependency's source exists: its path is there or its repository has the
+    /// branch or tag it follows. Versions are checked when resolving.
+    pub fn verify_source(&self, dependency: &Dependency) -> Result<(), ZeusProjectError> {
+        let invalid = |reason: String| ZeusProjectError::InvalidDependency {
+            name: dependency.name.clone(),
+            reason: reason
+        };
+
+        match dependency.source {
+            // Offline there's no telling, resolving will find out once zeus is online
+            Source::Git { ref url, reference: Some(ref reference) } if !self.config.offline() => {
+                if !try!(self.vcs.remote_has_ref(url, reference)) {
+                    return Err(invalid(format!("{} has no branch or tag named `{}`", auth::redact(url, &[]), reference)));
+                }
+            },
+            Source::Path(ref path) => {
+                if !self.get_file_path(path).exists() {
+                    return Err(invalid(format!("the path {} does not exist", path.display())));
+                }
+            },
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Athena followed by the game's own dependencies.
+    pub fn requirements(&self) -> Vec<Dependency> {
+        let mut requirements = vec![Dependency::athena(&self.manifest)];
+        requirements.extend(self.dependencies.iter().cloned());
+        requirements
+    }
+
+    /// Picks the versions to build with, sticking to Zeus.lock as long as it still fits the manifest.
+    /// Vendored projects never look anything up, Zeus.lock is all they have.
+    pub fn resolve(&self) -> Result<Resolve, ZeusProjectError> {
+        for dependency in self.dependencies.iter() {
+            if let Source::Path(_) = dependency.source {
+                try!(self.verify_source(dependency));
+            }
+        }
+
+        let requirements = self.requirements();
+        self.progress.report(&Progress::new(Phase::Resolve, &self.game_name, "Resolving dependencies"));
+
+        let locked = try!(lockfile::load(&self.get_file_path(lockfile::LOCKFILE)));
+        if let Some(ref locked) = locked {
+            if lock_fits(locked, &requirements) {
+                self.progress.report(&Progress::step(Phase::Resolve, &self.game_name, "Using Zeus.lock", 1, 1));
+                return Ok(locked.clone());
+            }
+        }
+
+        if let Some(vendor_dir) = self.vendor_dir() {
+            return Err(ZeusProjectError::ResolutionFailed(format!(
+                "Zeus.lock doesn't match Zeus.toml and the project only builds from {}, run `zeus vendor` to update it",
+                vendor_dir.display())));
+        }
+        if self.config.offline() {
+            return Err(ZeusProjectError::Offline(String::from("the versions Zeus.toml asks for, Zeus.lock doesn't match it")));
+        }
+
+        let mut resolve = try!(resolver::resolve(&self.game_name, &requirements, &mut SourceIndex::new(&self.directory, self.vcs.clone(), &self.config)));
+
+        // Packages that didn't change keep the commits and checksums they were locked with
+        if let Some(ref locked) = locked {
+            for package in resolve.packages.iter_mut() {
+                if let Some(previous) = locked.package(&package.name) {
+                    if previous.source == package.source && previous.version == package.version
+                        && previous.reference == package.reference {
+                        package.commit = previous.commit.clone();
+                        package.checksum = previous.checksum.clone();
+                    }
+                }
             }
+        }
 
-            // Actually remove the directory
-            fs::remove_dir_all(&athena_dir).unwrap();
+        self.progress.report(&Progress::step(Phase::Resolve, &self.game_name, "Resolved dependencies", 1, 1));
+        Ok(resolve)
+    }
+
+    /// Checks out Athena and every git dependency into `deps/`, registry packages are unpacked there.
+    /// Git packages are checked out at the commit they were locked at, the returned resolve has
+    /// the commits of those that weren't locked yet. Vendored projects only make sure everything
+    /// was vendored.
+    pub fn fetch(&self, resolve: &Resolve) -> Result<Resolve, ZeusProjectError> {
+        if let Some(vendor_dir) = self.vendor_dir() {
+            try!(self.check_vendored(&vendor_dir, resolve));
+            return Ok(resolve.clone());
         }
 
-        // Clone in the latest version of Athena
-        // TODO: Actually clone athena instead of zeus right now for testing
-        let athena_dir_str = athena_dir.to_str().unwrap();
-        git::clone("https://github.com/athena-org/zeus.git", athena_dir_str, "develop").unwrap();
+        let mut fetched = resolve.clone();
+        for package in fetched.packages.iter_mut() {
+            let progress = TaskProgress::new(&*self.progress, Phase::Fetch, &package.name);
+
+            // Offline whatever was fetched before has to do, verifying checks it's still what was locked
+            if self.config.offline() {
+                match package.source {
+                    Source::Git { .. } | Source::ZeusRegistry(_) => {
+                        let checkout_dir = self.checkout_dir(&package.name);
+                        if !checkout_dir.exists() {
+                            return Err(ZeusProjectError::Offline(format!("`{}`, it was never fetched", package.name)));
+                        }
+                        let git = match package.source { Source::Git { .. } => true, _ => false };
+                        if git && package.commit.is_none() {
+                            package.commit = Some(try!(self.vcs.rev_parse(&checkout_dir, "HEAD")));
+                        }
+                        continue;
+                    },
+                    Source::Path(_) | Source::Registry => continue
+                }
+            }
+
+            match package.source {
+                Source::Git { ref url, .. } => {
+                    let checkout_dir = self.checkout_dir(&package.name);
+                    let reference = package.reference.as_ref().map(|r| &r[..]).unwrap_or(dependency::DEFAULT_GIT_REF);
+
+                    let options = self.fetch_options(&package.name);
+
+                    // Delete the old checkout if it exists
+                    try!(remove_dir_forcefully(&checkout_dir));
+                    progress.report(&Progress::new(Phase::Fetch, "", &format!("Cloning {}", auth::redact(url, &[]))));
+                    try!(VcsBackend::clone(&*self.vcs, url, &checkout_dir, reference, &options, &progress));
+
+                    // Branches move on, what was locked is what gets checked out
+                    let head = try!(self.vcs.rev_parse(&checkout_dir, "HEAD"));
+                    match package.commit {
+                        Some(ref commit) if *commit != head => {
+                            // Shallow clones only have the tip, older commits get fetched by hash
+                            progress.report(&Progress::new(Phase::Fetch, "", &format!("Checking out {}", commit)));
+                            if self.vcs.checkout(&checkout_dir, commit, &options).is_err() {
+                                try!(self.vcs.fetch(&checkout_dir, commit, &progress));
+                                try!(self.vcs.checkout(&checkout_dir, commit, &options));
+                            }
+                        },
+                        Some(_) => {},
+                        None => package.commit = Some(head)
+                    }
+                },
+                Source::ZeusRegistry(ref location) => {
+                    let version = package.version.as_ref().expect("registry packages always have a version");
+                    let registry = Registry::open(location, &self.directory, &self.config);
+                    let entry = match try!(registry.entries(&package.name)).into_iter().find(|e| &e.version == version) {
+                        Some(entry) => entry,
+                        None => return Err(ZeusProjectError::InvalidDependency {
+                            name: package.name.clone(),
+                            reason: format!("version {} is no longer in the registry {}", version, auth::redact(location, &[]))
+                        })
+                    };
+
+                    // Downloads are checked against the index before anything gets unpacked
+                    let archive = self.get_file_path("target/packages")
+                        .join(format!("{}-{}.{}", package.name, version, registry::PACKAGE_EXTENSION));
+                    progress.report(&Progress::step(Phase::Fetch, "", "Downloading", 0, 2));
+                    try!(registry.download(&entry, &archive));
+
+                    progress.report(&Progress::step(Phase::Fetch, "", "Unpacking", 1, 2));
+                    let checkout_dir = self.checkout_dir(&package.name);
+                    try!(remove_dir_forcefully(&checkout_dir));
+                    try!(registry::unpack(&archive, &checkout_dir));
+                    progress.report(&Progress::step(Phase::Fetch, "", "Unpacked", 2, 2));
+                },
+                // Resolving made sure paths exist and cargo fetches registry crates itself
+                Source::Path(_) | Source::Registry => {}
+            }
+        }
+
+        Ok(fetched)
+    }
+
+    /// Checks every fetched package against the checksum it was locked with, returning the resolve
+    /// with the missing checksums filled in. With `update` mismatches are recorded instead.
+    pub fn verify(&self, resolve: &Resolve, update: bool) -> Result<Resolve, ZeusProjectError> {
+        let mut verified = resolve.clone();
+        let total = verified.packages.len() as u64;
+
+        for (i, package) in verified.packages.iter_mut().enumerate() {
+            self.progress.report(&Progress::step(Phase::Verify, &package.name, "Checking sources", i as u64, total));
+
+            match package.source {
+                Source::Git { .. } | Source::ZeusRegistry(_) => {},
+                // Paths are the user's own and cargo checks its crates itself
+                Source::Path(_) | Source::Registry => continue
+            }
+
+            let path = self.get_file_path(self.package_path(&package.name));
+            if !path.exists() {
+                return Err(ZeusProjectError::InvalidDependency {
+                    name: package.name.clone(),
+                    reason: format!("it hasn't been fetched into {}, run `zeus setup`", path.display())
+                });
+            }
+
+            let actual = try!(checksum::sha256_dir(&path));
+            match package.checksum {
+                Some(ref expected) if *expected != actual && !update => return Err(ZeusProjectError::ChecksumMismatch {
+                    path: path,
+                    expected: expected.clone(),
+                    actual: actual
+                }),
+                _ => {}
+            }
+            package.checksum = Some(actual);
+        }
+
+        self.progress.report(&Progress::step(Phase::Verify, &self.game_name, "Checked sources", total, total));
+        Ok(verified)
+    }
+
+    /// Copies Athena and every fetched dependency into `dir`, and cargo's crates into `dir/.crates`
+    /// when asked, so the project can be built without network access.
+    pub fn vendor(&self, dir: &Path, crates: bool) -> Result<Resolve, ZeusProjectError> {
+        // Vendoring again has to fetch fresh sources, whatever the manifest says
+        let mut online = try!(ZeusProject::open_with(self.directory.clone(), self.config.clone(), self.vcs.clone()));
+        online.set_progress(self.progress.clone());
+        if let toml::Value::Table(ref mut table) = online.manifest {
+            table.remove("vendor");
+        }
+        let resolve = try!(online.setup());
+
+        let vendor_dir = self.get_file_path(dir);
+        for package in resolve.packages.iter() {
+            match package.source {
+                Source::Git { .. } | Source::ZeusRegistry(_) => {
+                    let destination = vendor_dir.join(&package.name);
+                    try!(remove_dir_forcefully(&destination));
+                    try!(copy_sources(&online.checkout_dir(&package.name), &destination));
+                },
+                // Paths are already on disk and cargo's crates are vendored by cargo
+                Source::Path(_) | Source::Registry => {}
+            }
+        }
+
+        if crates {
+            let crates_dir = vendor_dir.join(VENDORED_CRATES);
+            try!(remove_dir_forcefully(&crates_dir));
+            let manifest_path = online.get_file_path(build::BUILD_DIR).join("Cargo.toml");
+            try!(cargo::vendor(&manifest_path, &crates_dir, try!(self.toolchain()).as_ref()));
+        }
+
+        Ok(resolve)
+    }
+
+    /// The rust toolchain the project pins in `[toolchain]`, if it pins one. A broken pin is an
+    /// error, building with whatever rustc is around would be the opposite of pinning.
+    pub fn toolchain(&self) -> Result<Option<Toolchain>, ZeusProjectError> {
+        Toolchain::from_manifest(&self.manifest)
+            .map_err(|_| ZeusProjectError::InvalidManifest { path: self.get_file_path("Zeus.toml"), errors: 1 })
+    }
+
+    /// The directory sources are vendored into, relative to the project. While it's set nothing
+    /// is fetched, everything is built out of it.
+    pub fn vendor_dir(&self) -> Option<PathBuf> {
+        self.manifest.lookup("vendor.directory").and_then(|v| v.as_str()).map(PathBuf::from)
+    }
+
+    /// Where cargo's crates are vendored, relative to the project, if they are.
+    pub fn vendored_crates_dir(&self) -> Option<PathBuf> {
+        match self.manifest.lookup("vendor.crates").and_then(|v| v.as_bool()) {
+            Some(true) => self.vendor_dir().map(|dir| dir.join(VENDORED_CRATES)),
+            _ => None
+        }
+    }
+
+    /// Where a package's sources are built from, relative to the project.
+    pub fn package_path(&self, name: &str) -> PathBuf {
+        match self.vendor_dir() {
+            Some(vendor_dir) => vendor_dir.join(name),
+            None if name == "athena" => PathBuf::from("athena"),
+            None => Path::new("deps").join(name)
+        }
+    }
+
+    fn check_vendored(&self, vendor_dir: &Path, resolve: &Resolve) -> Result<(), ZeusProjectError> {
+        for package in resolve.packages.iter() {
+            let vendored = match package.source {
+                Source::Git { .. } | Source::ZeusRegistry(_) => self.get_file_path(vendor_dir.join(&package.name)).exists(),
+                Source::Path(_) | Source::Registry => true
+            };
+
+            if !vendored {
+                return Err(ZeusProjectError::InvalidDependency {
+                    name: package.name.clone(),
+                    reason: format!("it isn't vendored in {}, run `zeus vendor` to vendor it", vendor_dir.display())
+                });
+            }
+        }
+
+        if let Some(crates_dir) = self.vendored_crates_dir() {
+            if !self.get_file_path(&crates_dir).exists() {
+                return Err(ZeusProjectError::InvalidDependency {
+                    name: String::from("crates"),
+                    reason: format!("cargo's crates aren't vendored in {}, run `zeus vendor --crates`", crates_dir.display())
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// What to fetch along with a git package, only the project's own requirements can ask for more.
+    pub fn fetch_options(&self, name: &str) -> FetchOptions {
+        self.requirements().into_iter()
+            .find(|requirement| requirement.name == name)
+            .map(|requirement| requirement.fetch)
+            .unwrap_or(FetchOptions::default())
+    }
+
+    /// Where a git package is checked out, Athena lives next to the game and the rest in `deps/`.
+    pub fn checkout_dir(&self, name: &str) -> PathBuf {
+        if name == "athena" {
+            self.get_file_path("athena")
+        } else {
+            self.get_file_path("deps").join(name)
+        }
+    }
+
+    /// Deletes a package's checkout, if there is one.
+    pub fn remove_checkout(&self, name: &str) -> Result<(), ZeusProjectError> {
+        remove_dir_forcefully(&self.checkout_dir(name))
+    }
+
+    /// A build profile from `[profile]`, with everything it inherits applied.
+    pub fn profile(&self, name: &str) -> Result<Profile, ZeusProjectError> {
+        Profile::resolve(&self.manifest, name)
+    }
+
+    /// The target a build cross-compiles for, none when it's for the host.
+    pub fn target(&self, options: &BuildOptions) -> Result<Option<Target>, ZeusProjectError> {
+        match options.target {
+            Some(ref triple) => Target::resolve(&self.manifest, triple).map(Some),
+            None => Ok(None)
+        }
+    }
+
+    /// The features a build turns on, checked against `[features]`. The profile's come first,
+    /// then the target's.
+    pub fn features(&self, profile: &Profile, target: Option<&Target>, options: &BuildOptions)
+        -> Result<Vec<String>, ZeusProjectError> {
+        let mut dependencies = vec!["athena"];
+        dependencies.extend(self.dependencies.iter().map(|dependency| &dependency.name[..]));
+
+        let mut requested = profile.features.clone();
+        if let Some(target) = target {
+            requested.extend(target.features.iter().cloned());
+        }
+        requested.extend(options.features.iter().cloned());
+        features::select(&self.manifest, &dependencies, &requested, options.default_features)
+    }
+
+    /// Writes the cargo project used to compile the game, set up for a build. Returns the path
+    /// of its Cargo.toml, the profile and the target it was set up for.
+    pub fn write_build_manifest(&self, options: &BuildOptions)
+        -> Result<(PathBuf, Profile, Option<Target>), ZeusProjectError> {
+        let profile = try!(self.profile(&options.profile));
+        let target = try!(self.target(options));
+        let features = try!(self.features(&profile, target.as_ref(), options));
+
+        let build_dir = self.get_file_path(build::BUILD_DIR);
+        try!(fs::create_dir_all(&build_dir).map_err(|e| ZeusProjectError::io(&build_dir, e)));
+
+        let manifest_name = Path::new(build::BUILD_DIR).join("Cargo.toml");
+        try!(self.create_file(&manifest_name, &build::cargo_manifest(self, &profile, &features)));
+
+        // Libraries from zeus registries ship without a Cargo.toml, theirs say where the project put
+        // their dependencies
+        let packages_dir = Path::new(build::BUILD_DIR).join(build::PACKAGES_DIR);
+        try!(remove_dir_forcefully(&self.get_file_path(&packages_dir)));
+        if let Some(resolve) = try!(lockfile::load(&self.get_file_path(lockfile::LOCKFILE))) {
+            for package in resolve.packages.iter() {
+                match package.source {
+                    Source::ZeusRegistry(_) if build::is_library(self, &package.name) => {},
+                    _ => continue
+                }
+
+                let package_dir = packages_dir.join(&package.name);
+                let path = self.get_file_path(&package_dir);
+                try!(fs::create_dir_all(&path).map_err(|e| ZeusProjectError::io(&path, e)));
+                try!(self.create_file(package_dir.join("Cargo.toml"), &build::package_manifest(self, package, &resolve)));
+            }
+        }
+
+        // Cargo only picks up source replacement and linkers from a .cargo directory it runs in
+        let config_dir = Path::new(build::BUILD_DIR).join(".cargo");
+        match build::cargo_config(self, target.as_ref()) {
+            Some(config) => {
+                let path = self.get_file_path(&config_dir);
+                try!(fs::create_dir_all(&path).map_err(|e| ZeusProjectError::io(&path, e)));
+                try!(self.create_file(config_dir.join("config"), &config));
+            },
+            None => try!(remove_dir_forcefully(&self.get_file_path(&config_dir)))
+        }
+
+        Ok((self.get_file_path(manifest_name), profile, target))
+    }
+
+    /// Compiles the game with the toolchain the project pins, which has to be installed already,
+    /// along with the standard library of the target when cross-compiling.
+    pub fn build_game(&self, options: &BuildOptions) -> Result<(), ZeusProjectError> {
+        let toolchain = try!(self.toolchain());
+        if let Some(ref toolchain) = toolchain {
+            try!(toolchain::ensure(toolchain, false));
+        }
+
+        let (manifest_path, profile, target) = try!(self.write_build_manifest(options));
+
+        // The profile and target reach the game as environment variables, for Athena to read with `env!`
+        let mut args = Vec::new();
+        let mut vars = profile.vars();
+        if profile.is_release() {
+            args.push(String::from("--release"));
+        }
+        if let Some(ref target) = target {
+            try!(target::ensure_std(target, toolchain.as_ref()));
+            args.push(String::from("--target"));
+            args.push(target.triple.clone());
+            vars.extend(target.vars());
+        }
+
+        let progress = TaskProgress::new(&*self.progress, Phase::Build, &self.game_name);
+        cargo::build(&manifest_path, &self.get_file_path(profile.target_dir()), &args, &vars,
+            toolchain.as_ref(), &progress)
+    }
+}
+
+// A lockfile stays valid while every requirement is still met by what it locked
+fn lock_fits(locked: &Resolve, requirements: &[Dependency]) -> bool {
+    let roots: Vec<&str> = requirements.iter().map(|r| &r.name[..]).collect();
+    if locked.roots.len() != roots.len() || !locked.roots.iter().all(|r| roots.contains(&&r[..])) {
+        return false;
+    }
+
+    requirements.iter().all(|requirement| {
+        let package = match locked.package(&requirement.name) {
+            Some(package) => package,
+            None => return false
+        };
+
+        match (requirement.requirement(), &package.version) {
+            (Some(wanted), &Some(ref version)) => wanted.matches(version) && match (&package.source, &requirement.source) {
+                (&Source::Git { url: ref a, .. }, &Source::Git { url: ref b, .. }) => a == b,
+                (a, b) => a == b
+            },
+            (None, &None) => package.source == requirement.source,
+            // Cargo picks crates.io versions, all the lock knows is what it was asked for
+            (Some(_), &None) if requirement.source == Source::Registry =>
+                package.source == Source::Registry && package.requirement == requirement.version,
+            _ => false
+        }
+    })
+}
+
+// Git marks its objects readonly, which stops remove_dir_all on some platforms
+fn remove_dir_forcefully(dir: &Path) -> Result<(), ZeusProjectError> {
+    if !dir.exists() { return Ok(()); }
+
+    // Set readonly on all files and directories to false
+    let walker = try!(fs::walk_dir(dir).map_err(|e| ZeusProjectError::io(dir, e)));
+    for file in walker {
+        let path = try!(file.map_err(|e| ZeusProjectError::io(dir, e))).path();
+
+        // Permissions would be set on whatever a link points at, which may not even be in here
+        if try!(fs::symlink_metadata(&path).map_err(|e| ZeusProjectError::io(&path, e))).file_type().is_symlink() {
+            continue;
+        }
+        let mut permissions = try!(fs::metadata(&path).map_err(|e| ZeusProjectError::io(&path, e))).permissions();
+        permissions.set_readonly(false);
+        try!(fs::set_permissions(&path, permissions).map_err(|e| ZeusProjectError::io(&path, e)));
+    }
+
+    // Actually remove the directory
+    fs::remove_dir_all(dir).map_err(|e| ZeusProjectError::io(dir, e))
+}
+
+// Copies a checkout without its git history, which is of no use to a vendored copy
+fn copy_sources(source: &Path, destination: &Path) -> Result<(), ZeusProjectError> {
+    try!(fs::create_dir_all(destination).map_err(|e| ZeusProjectError::io(destination, e)));
+
+    let entries = try!(fs::read_dir(source).map_err(|e| ZeusProjectError::io(source, e)));
+    for entry in entries {
+        let entry = try!(entry.map_err(|e| ZeusProjectError::io(source, e)));
+        if entry.file_name() == ".git" { continue; }
+
+        let from = entry.path();
+        let to = destination.join(entry.file_name());
+        let metadata = try!(fs::symlink_metadata(&from).map_err(|e| ZeusProjectError::io(&from, e)));
+        if metadata.file_type().is_symlink() {
+            // Copied as links, they hash the same as the checkout's and can't loop
+            let target = try!(fs::read_link(&from).map_err(|e| ZeusProjectError::io(&from, e)));
+            try!(copy_symlink(&target, &from, &to).map_err(|e| ZeusProjectError::io(&to, e)));
+        } else if metadata.is_dir() {
+            try!(copy_sources(&from, &to));
+        } else {
+            try!(fs::copy(&from, &to).map_err(|e| ZeusProjectError::io(&from, e)));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(target: &Path, _: &Path, link: &Path) -> io::Result<()> {
+    ::std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn copy_symlink(target: &Path, original: &Path, link: &Path) -> io::Result<()> {
+    // Windows links know whether they point at a directory, broken ones count as files
+    if fs::metadata(original).map(|metadata| metadata.is_dir()).unwrap_or(false) {
+        ::std::os::windows::fs::symlink_dir(target, link)
+    } else {
+        ::std::os::windows::fs::symlink_file(target, link)
     }
 }
 
+fn parent_dir(target_dir: &Path) -> PathBuf {
+    match target_dir.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from(".")
+    }
+}
+
+// Creates the directory a new project goes in, returning the directories that weren't there
+// before, deepest first
+fn create_parents(target_dir: &Path) -> Result<Vec<PathBuf>, ZeusProjectError> {
+    let parent = parent_dir(target_dir);
+
+    let mut created = Vec::new();
+    let mut dir = Some(parent.as_path());
+    while let Some(missing) = dir {
+        if missing.as_os_str().is_empty() || missing.exists() { break; }
+        created.push(missing.to_path_buf());
+        dir = missing.parent();
+    }
+
+    match fs::create_dir_all(&parent) {
+        Ok(_) => Ok(created),
+        Err(e) => {
+            remove_created(&created);
+            Err(ZeusProjectError::io(&parent, e))
+        }
+    }
+}
+
+fn remove_created(created: &[PathBuf]) {
+    for dir in created {
+        let _ = fs::remove_dir(dir);
+    }
+}
+
+// Never reuses a directory that's already there, whoever it belongs to
+fn create_staging_dir(target_dir: &Path, name: &OsStr) -> Result<PathBuf, ZeusProjectError> {
+    let parent = parent_dir(target_dir);
+
+    let mut attempt = 0u32;
+    loop {
+        let mut staging_name = OsString::from(".");
+        staging_name.push(name);
+        staging_name.push(format!(".zeus-staging-{}", attempt));
+
+        let staging_dir = parent.join(staging_name);
+        match fs::create_dir(&staging_dir) {
+            Ok(_) => return Ok(staging_dir),
+            Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => attempt += 1,
+            Err(e) => return Err(ZeusProjectError::io(&staging_dir, e))
+        }
+    }
+}
+
+fn move_into_place(staging_dir: &Path, target_dir: &Path, existing: Option<&fs::Permissions>) -> Result<(), ZeusProjectError> {
+    // An existing target is known to be empty at this point, rename can't replace directories everywhere
+    if existing.is_some() {
+        try!(fs::remove_dir(target_dir).map_err(|e| ZeusProjectError::io(target_dir, e)));
+    }
+
+    match fs::rename(staging_dir, target_dir) {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            if let Some(permissions) = existing {
+                let _ = restore_empty_dir(target_dir, permissions);
+            }
+            Err(ZeusProjectError::io(target_dir, e))
+        }
+    }
+}
+
+// Takes a project that was moved into place back out, leaving the target as it was found
+fn restore_target(target_dir: &Path, existing: Option<&fs::Permissions>) -> io::Result<()> {
+    try!(fs::remove_dir_all(target_dir));
+    match existing {
+        Some(permissions) => restore_empty_dir(target_dir, permissions),
+        None => Ok(())
+    }
+}
+
+fn restore_empty_dir(dir: &Path, permissions: &fs::Permissions) -> io::Result<()> {
+    try!(fs::create_dir(dir));
+    fs::set_permissions(dir, permissions.clone())
+}
+
 mod io_utils {
     use std;
     use std::fs::*;
-    use std::io::{Read, Write};
-    use std::path::PathBuf;
+    use std::io::{ErrorKind, Read, Write};
+    use std::path::{Path, PathBuf};
     use project;
+    use project::ZeusProjectError;
 
     impl project::ZeusProject {
-        pub fn get_file_path(&self, name: &str) -> PathBuf {
+        pub fn get_file_path<P: AsRef<Path>>(&self, name: P) -> PathBuf {
             let mut path = self.directory().clone();
             path.push(name);
             path
         }
 
-        pub fn file_exists(&self, name: &str) -> bool {
+        pub fn file_exists<P: AsRef<Path>>(&self, name: P) -> Result<bool, ZeusProjectError> {
             let path = self.get_file_path(name);
-            path.exists()
+            match metadata(&path) {
+                Ok(_) => Ok(true),
+                Err(ref e) if e.kind() == ErrorKind::NotFound => Ok(false),
+                Err(e) => Err(ZeusProjectError::io(&path, e))
+            }
         }
 
-        pub fn create_file(&self, name: &str, data: &str) {
+        pub fn create_file<P: AsRef<Path>>(&self, name: P, data: &str) -> Result<(), ZeusProjectError> {
             let path = self.get_file_path(name);
-            let mut file = File::create(path).unwrap();
-            file.write_all(&data.as_bytes()).unwrap();
+            let mut file = try!(File::create(&path).map_err(|e| ZeusProjectError::io(&path, e)));
+            file.write_all(&data.as_bytes()).map_err(|e| ZeusProjectError::io(&path, e))
         }
 
-        pub fn parse_file<T: std::str::FromStr>(&self, name: &str) -> Result<T, project::ZeusProjectError> {
+        pub fn parse_file<T: std::str::FromStr, P: AsRef<Path>>(&self, name: P) -> Result<T, ZeusProjectError> {
             let path = self.get_file_path(name);
-            let mut file = File::open(path.clone()).unwrap();
+            let mut file = try!(File::open(&path).map_err(|e| ZeusProjectError::io(&path, e)));
             let mut file_data = String::new();
-            file.read_to_string(&mut file_data).unwrap();
+            try!(file.read_to_string(&mut file_data).map_err(|e| ZeusProjectError::io(&path, e)));
 
             match file_data.parse() {
                 Ok(v) => Ok(v),
-                Err(_) => Err(project::ZeusProjectError::CorruptedFile(String::from(path.to_str().unwrap())))
+                Err(_) => Err(ZeusProjectError::CorruptedFile(path))
             }
         }
     }
 } pub use self::io_utils::*;
+
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::fs::File;
+    use std::io::{Read, Write};
+    use std::path::{Path, PathBuf};
+    use std::rc::Rc;
+
+    use config::Config;
+    use dependency;
+    use lockfile;
+    use vcs::FakeVcs;
+    use super::{ZeusProject, ZeusProjectError};
+
+    static PHYSICS_GIT: &'static str = "https://example.com/physics.git";
+
+    // A fresh project directory with the given manifest, named after the test using it
+    fn project_dir(test: &str, manifest: &str) -> PathBuf {
+        let directory = env::temp_dir().join(format!("zeus-test-{}", test));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        File::create(directory.join("Zeus.toml")).unwrap().write_all(manifest.as_bytes()).unwrap();
+        directory
+    }
+
+    fn manifest(dependencies: &str) -> String {
+        format!("manifest_version = 2\n\n[game]\nname = \"test\"\nversion = \"0.0.1\"\n\n\
+                 [athena]\nversion = \"develop\"\n{}", dependencies)
+    }
+
+    fn read(path: &Path) -> String {
+        let mut contents = String::new();
+        File::open(path).unwrap().read_to_string(&mut contents).unwrap();
+        contents
+    }
+
+    // Default settings keep the tests away from the user's config and ZEUS_* variables
+    fn open(directory: &Path, vcs: &Rc<FakeVcs>) -> ZeusProject {
+        ZeusProject::open_with(directory.to_path_buf(), Config::default(), vcs.clone()).unwrap()
+    }
+
+    #[test]
+    fn open_needs_a_manifest() {
+        let directory = project_dir("no-manifest", "");
+        fs::remove_file(directory.join("Zeus.toml")).unwrap();
+
+        match ZeusProject::open_with(directory, Config::default(), Rc::new(FakeVcs::new())) {
+            Err(ZeusProjectError::NotAZeusProject) => {},
+            other => panic!("expected NotAZeusProject, got {:?}", other.map(|p| p.game_name))
+        }
+    }
+
+    #[test]
+    fn setup_locks_the_commit_athena_was_checked_out_at() {
+        let directory = project_dir("lock-commit", &manifest(""));
+        let vcs = Rc::new(FakeVcs::new());
+        let commit = vcs.add_branch(dependency::ATHENA_GIT, "develop", &[("README.md", "first")]);
+
+        let project = open(&directory, &vcs);
+        project.setup().unwrap();
+
+        let locked = lockfile::load(&directory.join(lockfile::LOCKFILE)).unwrap().unwrap();
+        assert_eq!(locked.package("athena").unwrap().commit, Some(commit));
+        assert_eq!(read(&project.checkout_dir("athena").join("README.md")), "first");
+    }
+
+    #[test]
+    fn setup_sticks_to_the_locked_commit_when_the_branch_moves_on() {
+        let directory = project_dir("moved-branch", &manifest(""));
+        let vcs = Rc::new(FakeVcs::new());
+        let commit = vcs.add_branch(dependency::ATHENA_GIT, "develop", &[("README.md", "first")]);
+
+        let project = open(&directory, &vcs);
+        project.setup().unwrap();
+        vcs.add_branch(dependency::ATHENA_GIT, "develop", &[("README.md", "second")]);
+        let resolve = project.setup().unwrap();
+
+        assert_eq!(resolve.package("athena").unwrap().commit, Some(commit.clone()));
+        assert_eq!(read(&project.checkout_dir("athena").join("README.md")), "first");
+        assert!(vcs.calls().iter().any(|call| call.starts_with("checkout ") && call.ends_with(&commit[..])));
+    }
+
+    #[test]
+    fn setup_picks_the_newest_tag_matching_the_requirement() {
+        let directory = project_dir("newest-tag", &manifest(&format!(
+            "\n[dependencies.physics]\ngit = \"{}\"\nversion = \"^1.0\"\n", PHYSICS_GIT)));
+        let vcs = Rc::new(FakeVcs::new());
+        vcs.add_branch(dependency::ATHENA_GIT, "develop", &[("README.md", "athena")]);
+        vcs.add_tag(PHYSICS_GIT, "v1.0.0", &[("README.md", "1.0.0")]);
+        vcs.add_tag(PHYSICS_GIT, "v1.2.0", &[("README.md", "1.2.0")]);
+        vcs.add_tag(PHYSICS_GIT, "v2.0.0", &[("README.md", "2.0.0")]);
+
+        let project = open(&directory, &vcs);
+        let resolve = project.setup().unwrap();
+
+        let physics = resolve.package("physics").unwrap();
+        assert_eq!(physics.reference, Some(String::from("v1.2.0")));
+        assert_eq!(read(&project.checkout_dir("physics").join("README.md")), "1.2.0");
+    }
+
+    #[test]
+    fn resolving_a_locked_project_stays_off_the_network() {
+        let directory = project_dir("locked-resolve", &manifest(&format!(
+            "\n[dependencies.physics]\ngit = \"{}\"\nversion = \"^1.0\"\n", PHYSICS_GIT)));
+        let vcs = Rc::new(FakeVcs::new());
+        vcs.add_branch(dependency::ATHENA_GIT, "develop", &[("README.md", "athena")]);
+        vcs.add_tag(PHYSICS_GIT, "v1.0.0", &[("README.md", "1.0.0")]);
+
+        let project = open(&directory, &vcs);
+        project.setup().unwrap();
+        let calls = vcs.calls().len();
+        project.resolve().unwrap();
+
+        assert_eq!(vcs.calls().len(), calls);
+    }
+}
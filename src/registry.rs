@@ -0,0 +1,589 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Zeus packages and the registries they're published to.
+//!
+//! A registry is a directory (or the same layout served over HTTP) holding an index file per
+//! package and the package archives themselves:
+//!
+//! ```text
+//! index/names
+//! index/latest.toml
+//! index/<name>.toml
+//! packages/<name>/<name>-<version>.zeuspkg
+//! ```
+//!
+//! `index/names` lists every package, one per line, since HTTP has no way of listing a directory.
+//! `index/latest.toml` has the newest version of every package, so searching takes a single request.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use semver::Version;
+use tar::Archive;
+use toml::{Parser, Table, Value};
+
+use auth;
+use build;
+use checksum;
+use config::Config;
+use dependency;
+use dependency::{Dependency, Source};
+use manifest;
+use manifest::editor::format_value;
+use project::{ZeusProject, ZeusProjectError};
+use resolver::{PackageIndex, Summary};
+
+pub static PACKAGE_EXTENSION: &'static str = "zeuspkg";
+
+// Generated or fetched, never part of a package
+static EXCLUDED: &'static [&'static str] = &[".git", ".zeus", "athena", "deps", "target", "Zeus.lock"];
+
+static CURL_PATH: &'static str = "curl";
+
+static NAMES_PATH: &'static str = "index/names";
+
+static LATEST_PATH: &'static str = "index/latest.toml";
+
+/// Where index files from HTTP registries are cached, relative to the `cache.dir` setting.
+static CACHE_DIR: &'static str = "registry";
+
+// ### Index ###
+
+/// One published version of a package.
+#[derive(Debug, Clone)]
+pub struct IndexEntry {
+    pub name: String,
+    pub version: Version,
+    pub checksum: String,
+    pub description: Option<String>,
+    pub keywords: Vec<String>,
+    pub dependencies: Vec<Dependency>
+}
+
+impl IndexEntry {
+    fn render(&self) -> String {
+        let mut dependencies = Table::new();
+        for dependency in self.dependencies.iter() {
+            dependencies.insert(dependency.name.clone(), dependency.to_value());
+        }
+
+        let mut lines = vec![
+            String::new(),
+            String::from("[[version]]"),
+            format!("name = {}", Value::String(self.name.clone())),
+            format!("version = {}", Value::String(self.version.to_string())),
+            format!("checksum = {}", Value::String(self.checksum.clone()))
+        ];
+        if let Some(ref description) = self.description {
+            lines.push(format!("description = {}", Value::String(description.clone())));
+        }
+        let keywords = self.keywords.iter().map(|k| Value::String(k.clone())).collect();
+        lines.push(format!("keywords = {}", format_value(&Value::Array(keywords))));
+        lines.push(format!("dependencies = {}", format_value(&Value::Table(dependencies))));
+        lines.push(String::new());
+
+        lines.join("\n")
+    }
+
+    // Entries name their package, those written before they did belong to `name`
+    fn parse_all(name: &str, source: &str) -> Option<Vec<IndexEntry>> {
+        let root = match Parser::new(source).parse() {
+            Some(root) => Value::Table(root),
+            None => return None
+        };
+
+        let mut entries = Vec::new();
+        for entry in root.lookup("version").and_then(|v| v.as_slice()).unwrap_or(&[]) {
+            let version = match entry.lookup("version").and_then(|v| v.as_str()).map(Version::parse) {
+                Some(Ok(version)) => version,
+                _ => return None
+            };
+            let checksum = match entry.lookup("checksum").and_then(|v| v.as_str()) {
+                Some(checksum) => String::from(checksum),
+                None => return None
+            };
+            let dependencies = match entry.lookup("dependencies") {
+                Some(&Value::Table(ref table)) => match dependency::parse_dependencies(table) {
+                    Ok(dependencies) => dependencies,
+                    Err(_) => return None
+                },
+                Some(_) => return None,
+                None => Vec::new()
+            };
+
+            entries.push(IndexEntry {
+                name: String::from(entry.lookup("name").and_then(|v| v.as_str()).unwrap_or(name)),
+                version: version,
+                checksum: checksum,
+                description: entry.lookup("description").and_then(|v| v.as_str()).map(String::from),
+                keywords: entry.lookup("keywords")
+                    .and_then(|v| v.as_slice())
+                    .map(|keywords| keywords.iter().filter_map(|k| k.as_str()).map(String::from).collect())
+                    .unwrap_or(Vec::new()),
+                dependencies: dependencies
+            });
+        }
+
+        Some(entries)
+    }
+}
+
+
+// ### Registry ###
+
+#[derive(Debug, Clone)]
+enum Location {
+    Directory(PathBuf),
+    Http(String)
+}
+
+#[derive(Debug, Clone)]
+pub struct Registry {
+    location: Location,
+    /// Where index files fetched over HTTP are kept, for when the registry can't be reached.
+    cache: Option<PathBuf>,
+    /// Only read from the cache, never from the registry itself.
+    offline: bool
+}
+
+impl Registry {
+    /// Opens a registry by directory or HTTP url, relative directories are relative to `base`.
+    /// The config decides where HTTP registries are cached and whether they're reached at all.
+    pub fn open(registry: &str, base: &Path, config: &Config) -> Registry {
+        let location = if registry.starts_with("http://") || registry.starts_with("https://") {
+            Location::Http(String::from(registry.trim_right_matches('/')))
+        } else if registry.starts_with("file://") {
+            Location::Directory(base.join(&registry[7..]))
+        } else {
+            Location::Directory(base.join(registry))
+        };
+
+        let cache = match location {
+            Location::Http(ref url) => config.cache_dir().map(|dir| dir.join(CACHE_DIR).join(cache_name(url))),
+            Location::Directory(_) => None
+        };
+
+        Registry { location: location, cache: cache, offline: config.offline() }
+    }
+
+    /// Opens a registry named on the command line or in the config, relative directories are
+    /// relative to where zeus was run from rather than to the project.
+    pub fn open_from_working_dir(registry: &str, config: &Config) -> Result<Registry, ZeusProjectError> {
+        let base = try!(env::current_dir().map_err(|e| ZeusProjectError::io(Path::new("."), e)));
+        Ok(Registry::open(registry, &base, config))
+    }
+
+    /// The name of every package in the registry, sorted.
+    pub fn names(&self) -> Result<Vec<String>, ZeusProjectError> {
+        let mut names: Vec<String> = match try!(self.read(NAMES_PATH)) {
+            Some(source) => source.lines().map(|l| l.trim()).filter(|l| !l.is_empty()).map(String::from).collect(),
+            None => Vec::new()
+        };
+
+        names.sort();
+        names.dedup();
+        Ok(names)
+    }
+
+    /// The newest version of every package whose name, description or keywords mention the query.
+    pub fn search(&self, query: &str) -> Result<Vec<IndexEntry>, ZeusProjectError> {
+        let query = query.to_lowercase();
+        let latest = try!(self.latest());
+
+        Ok(latest.into_iter().filter(|entry| {
+            entry.name.to_lowercase().contains(&query)
+                || entry.description.as_ref().map_or(false, |d| d.to_lowercase().contains(&query))
+                || entry.keywords.iter().any(|k| k.to_lowercase().contains(&query))
+        }).collect())
+    }
+
+    /// The newest version of every package, sorted by name.
+    pub fn latest(&self) -> Result<Vec<IndexEntry>, ZeusProjectError> {
+        if let Some(source) = try!(self.read(LATEST_PATH)) {
+            return match IndexEntry::parse_all("", &source) {
+                Some(entries) => Ok(entries.into_iter().filter(|entry| !entry.name.is_empty()).collect()),
+                None => Err(ZeusProjectError::CorruptedFile(PathBuf::from(self.describe(LATEST_PATH))))
+            };
+        }
+
+        // Registries from before index/latest.toml only have the package's own index files
+        let mut latest = Vec::new();
+        for name in try!(self.names()) {
+            if let Some(entry) = newest(try!(self.entries(&name))) {
+                latest.push(entry);
+            }
+        }
+
+        Ok(latest)
+    }
+
+    /// Every published version of a package, none if the registry doesn't know it.
+    pub fn entries(&self, name: &str) -> Result<Vec<IndexEntry>, ZeusProjectError> {
+        let relative = index_path(name);
+        let source = match try!(self.read(&relative)) {
+            Some(source) => source,
+            None => return Ok(Vec::new())
+        };
+
+        match IndexEntry::parse_all(name, &source) {
+            Some(entries) => Ok(entries),
+            None => Err(ZeusProjectError::CorruptedFile(PathBuf::from(self.describe(&relative))))
+        }
+    }
+
+    /// Copies a package archive to `destination`, making sure it's the one that was published.
+    pub fn download(&self, entry: &IndexEntry, destination: &Path) -> Result<(), ZeusProjectError> {
+        if let Some(parent) = destination.parent() {
+            try!(fs::create_dir_all(parent).map_err(|e| ZeusProjectError::io(parent, e)));
+        }
+
+        let relative = archive_path(&entry.name, &entry.version);
+        match self.location {
+            Location::Directory(ref root) => {
+                let source = root.join(&relative);
+                try!(fs::copy(&source, destination).map_err(|e| ZeusProjectError::io(&source, e)));
+            },
+            // Offline, an earlier download is all there is to go on
+            Location::Http(ref url) if self.offline => if !destination.exists() {
+                return Err(ZeusProjectError::Offline(auth::redact(&format!("{}/{}", url, relative), &[])));
+            },
+            Location::Http(ref url) => try!(curl(&format!("{}/{}", url, relative), Some(destination)).map(|_| ()))
+        }
+
+        checksum::verify_file(destination, &entry.checksum)
+    }
+
+    /// Fails unless packages can be published to the registry, HTTP registries are read only.
+    pub fn check_publishable(&self) -> Result<(), ZeusProjectError> {
+        match self.location {
+            Location::Directory(_) => Ok(()),
+            Location::Http(ref url) => Err(ZeusProjectError::UnsupportedRegistry(auth::redact(url, &[])))
+        }
+    }
+
+    /// Adds a package to the registry, only directory registries can be published to.
+    pub fn publish(&self, package: &Package) -> Result<(), ZeusProjectError> {
+        let root = match self.location {
+            Location::Directory(ref root) => root,
+            Location::Http(ref url) => return Err(ZeusProjectError::UnsupportedRegistry(auth::redact(url, &[])))
+        };
+
+        let entry = &package.entry;
+        if try!(self.entries(&entry.name)).iter().any(|e| e.version == entry.version) {
+            return Err(ZeusProjectError::AlreadyPublished {
+                name: entry.name.clone(),
+                version: entry.version.to_string()
+            });
+        }
+
+        // The archive goes in first so the index never points at something missing
+        let archive = root.join(archive_path(&entry.name, &entry.version));
+        let archive_dir = archive.parent().expect("archives always live in a directory").to_path_buf();
+        try!(fs::create_dir_all(&archive_dir).map_err(|e| ZeusProjectError::io(&archive_dir, e)));
+        try!(fs::copy(&package.path, &archive).map_err(|e| ZeusProjectError::io(&archive, e)));
+
+        let index = root.join(index_path(&entry.name));
+        let index_dir = index.parent().expect("index files always live in a directory").to_path_buf();
+        try!(fs::create_dir_all(&index_dir).map_err(|e| ZeusProjectError::io(&index_dir, e)));
+        let mut file = try!(OpenOptions::new().create(true).append(true).open(&index)
+            .map_err(|e| ZeusProjectError::io(&index, e)));
+        try!(file.write_all(entry.render().as_bytes()).map_err(|e| ZeusProjectError::io(&index, e)));
+
+        // Search reads the newest versions from a single file, so it has to stay up to date
+        let mut latest: Vec<IndexEntry> = try!(self.latest()).into_iter().filter(|e| e.name != entry.name).collect();
+        latest.extend(newest(try!(self.entries(&entry.name))));
+        latest.sort_by(|a, b| a.name.cmp(&b.name));
+        let latest_path = root.join(LATEST_PATH);
+        let rendered: Vec<String> = latest.iter().map(|e| e.render()).collect();
+        try!(write_file(&latest_path, &rendered.concat()));
+
+        let mut names = try!(self.names());
+        if !names.contains(&entry.name) {
+            names.push(entry.name.clone());
+            names.sort();
+
+            let names_path = root.join(NAMES_PATH);
+            let mut file = try!(File::create(&names_path).map_err(|e| ZeusProjectError::io(&names_path, e)));
+            try!(file.write_all((names.join("\n") + "\n").as_bytes()).map_err(|e| ZeusProjectError::io(&names_path, e)));
+        }
+
+        Ok(())
+    }
+
+    fn read(&self, relative: &str) -> Result<Option<String>, ZeusProjectError> {
+        let url = match self.location {
+            Location::Directory(ref root) => return read_file(&root.join(relative)),
+            Location::Http(ref url) => format!("{}/{}", url, relative)
+        };
+
+        let cached = self.cache.as_ref().map(|cache| cache.join(relative));
+        let fetched = if self.offline { Err(ZeusProjectError::Offline(url.clone())) } else { curl(&url, None) };
+        match fetched {
+            Ok(source) => {
+                // Caching is best effort, a registry that can be reached doesn't need it
+                if let (Some(ref cached), Some(ref source)) = (cached, source.as_ref()) {
+                    let _ = write_file(cached, source);
+                }
+                Ok(source)
+            },
+            Err(error) => match cached {
+                Some(ref cached) => match try!(read_file(cached)) {
+                    Some(source) => Ok(Some(source)),
+                    None => Err(error)
+                },
+                None => Err(error)
+            }
+        }
+    }
+
+    fn describe(&self, relative: &str) -> String {
+        match self.location {
+            Location::Directory(ref root) => root.join(relative).display().to_string(),
+            Location::Http(ref url) => format!("{}/{}", url, relative)
+        }
+    }
+}
+
+fn newest(entries: Vec<IndexEntry>) -> Option<IndexEntry> {
+    entries.into_iter().fold(None, |newest: Option<IndexEntry>, entry| match newest {
+        Some(ref newest) if newest.version > entry.version => Some(newest.clone()),
+        _ => Some(entry)
+    })
+}
+
+fn read_file(path: &Path) -> Result<Option<String>, ZeusProjectError> {
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(ref e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(ZeusProjectError::io(path, e))
+    };
+
+    let mut source = String::new();
+    try!(file.read_to_string(&mut source).map_err(|e| ZeusProjectError::io(path, e)));
+    Ok(Some(source))
+}
+
+fn write_file(path: &Path, source: &str) -> Result<(), ZeusProjectError> {
+    if let Some(parent) = path.parent() {
+        try!(fs::create_dir_all(parent).map_err(|e| ZeusProjectError::io(parent, e)));
+    }
+
+    let mut file = try!(File::create(path).map_err(|e| ZeusProjectError::io(path, e)));
+    file.write_all(source.as_bytes()).map_err(|e| ZeusProjectError::io(path, e))
+}
+
+// One cache directory per registry, named after its url
+fn cache_name(url: &str) -> String {
+    let name = url.splitn(2, "://").last().unwrap_or(url);
+    name.chars().map(|c| if c.is_alphanumeric() || c == '.' || c == '-' { c } else { '_' }).collect()
+}
+
+fn index_path(name: &str) -> String {
+    format!("index/{}.toml", name)
+}
+
+fn archive_path(name: &str, version: &Version) -> String {
+    format!("packages/{}/{}-{}.{}", name, name, version, PACKAGE_EXTENSION)
+}
+
+// Fetches a url, into a file when given one. Missing files come back as None.
+fn curl(url: &str, output: Option<&Path>) -> Result<Option<String>, ZeusProjectError> {
+    let mut command = Command::new(CURL_PATH);
+    command.args(&["--fail", "--silent", "--show-error", "--location", "--write-out", "\n%{http_code}"]);
+    if let Some(output) = output {
+        command.arg("--output").arg(output);
+    }
+
+    let result = try!(command.arg(url)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| ZeusProjectError::io(Path::new(CURL_PATH), e)));
+
+    // --write-out puts the HTTP status on a line of its own after the body
+    let stdout = String::from_utf8_lossy(&result.stdout).into_owned();
+    let (body, status) = match stdout.rfind('\n') {
+        Some(index) => (&stdout[..index], stdout[index + 1..].trim()),
+        None => ("", stdout.trim())
+    };
+
+    match (result.status.code(), status) {
+        (Some(0), _) => Ok(Some(String::from(body))),
+        (_, "404") | (_, "410") if output.is_none() => Ok(None),
+        (_, "401") | (_, "403") if auth::host_of(url).is_some() => {
+            let host = auth::host_of(url).unwrap();
+            let hint = auth::auth_hint(&host);
+            Err(ZeusProjectError::AuthenticationFailed { host: host, hint: hint })
+        },
+        _ => Err(ZeusProjectError::CommandFailed {
+            command: auth::redact(&format!("{} {}", CURL_PATH, url), &[]),
+            output: auth::redact(&String::from_utf8_lossy(&result.stderr), &[])
+        })
+    }
+}
+
+// ### Packaging ###
+
+/// A package archive ready to be published.
+#[derive(Debug, Clone)]
+pub struct Package {
+    pub path: PathBuf,
+    pub entry: IndexEntry
+}
+
+/// Packs a project into `target/package/<name>-<version>.zeuspkg`.
+pub fn package(project: &ZeusProject) -> Result<Package, ZeusProjectError> {
+    // Only valid manifests get published
+    let manifest_path = project.get_file_path("Zeus.toml");
+    let errors = try!(manifest::check_file(&manifest_path)).iter().filter(|d| d.is_error()).count();
+    if errors != 0 {
+        return Err(ZeusProjectError::InvalidManifest { path: manifest_path, errors: errors });
+    }
+
+    let manifest = project.manifest();
+    let name = build::crate_name(project.game_name());
+    let version = match manifest.lookup("game.version").and_then(|v| v.as_str()).map(Version::parse) {
+        Some(Ok(version)) => version,
+        _ => return Err(ZeusProjectError::CorruptedFile(manifest_path))
+    };
+
+    // Whoever uses the package has to be able to resolve everything it needs
+    let mut dependencies = Vec::new();
+    let athena = Dependency::athena(manifest);
+    if athena.version.is_some() {
+        dependencies.push(athena);
+    }
+    for dependency in project.dependencies() {
+        if let Source::Path(_) = dependency.source {
+            return Err(ZeusProjectError::InvalidDependency {
+                name: dependency.name.clone(),
+                reason: String::from("path dependencies can't be part of a published package")
+            });
+        }
+        dependencies.push(dependency.clone());
+    }
+
+    // Pack it all up
+    let output_dir = project.get_file_path("target/package");
+    try!(fs::create_dir_all(&output_dir).map_err(|e| ZeusProjectError::io(&output_dir, e)));
+    let path = output_dir.join(format!("{}-{}.{}", name, version, PACKAGE_EXTENSION));
+
+    let mut files = Vec::new();
+    try!(package_files(project.directory(), Path::new(""), &mut files));
+
+    let archive = Archive::new(try!(File::create(&path).map_err(|e| ZeusProjectError::io(&path, e))));
+    for relative in files.iter() {
+        let source = project.get_file_path(relative);
+        let mut file = try!(File::open(&source).map_err(|e| ZeusProjectError::io(&source, e)));
+        try!(archive.append_file(relative, &mut file).map_err(|e| ZeusProjectError::io(&path, e)));
+    }
+    try!(archive.finish().map_err(|e| ZeusProjectError::io(&path, e)));
+
+    let checksum = try!(checksum::sha256_file(&path));
+    Ok(Package {
+        path: path,
+        entry: IndexEntry {
+            name: name,
+            version: version,
+            checksum: checksum,
+            description: manifest.lookup("game.description").and_then(|v| v.as_str()).map(String::from),
+            keywords: manifest.lookup("game.keywords")
+                .and_then(|v| v.as_slice())
+                .map(|keywords| keywords.iter().filter_map(|k| k.as_str()).map(String::from).collect())
+                .unwrap_or(Vec::new()),
+            dependencies: dependencies
+        }
+    })
+}
+
+/// Unpacks a package archive into a directory.
+pub fn unpack(archive: &Path, destination: &Path) -> Result<(), ZeusProjectError> {
+    try!(fs::create_dir_all(destination).map_err(|e| ZeusProjectError::io(destination, e)));
+
+    let file = try!(File::open(archive).map_err(|e| ZeusProjectError::io(archive, e)));
+    Archive::new(file).unpack(destination).map_err(|e| ZeusProjectError::io(archive, e))
+}
+
+// Every file of the project worth packaging, relative to its directory
+fn package_files(root: &Path, relative: &Path, files: &mut Vec<PathBuf>) -> Result<(), ZeusProjectError> {
+    let directory = root.join(relative);
+    let entries = try!(fs::read_dir(&directory).map_err(|e| ZeusProjectError::io(&directory, e)));
+
+    for entry in entries {
+        let entry = try!(entry.map_err(|e| ZeusProjectError::io(&directory, e)));
+        let name = entry.file_name();
+        let name_str = name.to_string_lossy();
+
+        let excluded = relative.as_os_str().is_empty() && EXCLUDED.contains(&&name_str[..]);
+        if excluded || name_str.ends_with(&format!(".{}", PACKAGE_EXTENSION)) {
+            continue;
+        }
+
+        let path = relative.join(&name);
+        let metadata = try!(entry.metadata().map_err(|e| ZeusProjectError::io(&entry.path(), e)));
+        if metadata.is_dir() {
+            try!(package_files(root, &path, files));
+        } else {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+
+// ### Resolving ###
+
+/// Finds versions in zeus registries, registry locations are relative to the project.
+pub struct RegistryIndex {
+    base: PathBuf,
+    config: Config,
+    cache: HashMap<(String, String), Vec<IndexEntry>>
+}
+
+impl RegistryIndex {
+    pub fn new(base: &Path, config: &Config) -> RegistryIndex {
+        RegistryIndex {
+            base: base.to_path_buf(),
+            config: config.clone(),
+            cache: HashMap::new()
+        }
+    }
+}
+
+impl PackageIndex for RegistryIndex {
+    fn summaries(&mut self, dependency: &Dependency) -> Result<Vec<Summary>, ZeusProjectError> {
+        let registry = match dependency.source {
+            Source::ZeusRegistry(ref registry) => registry,
+            _ => return Ok(Vec::new())
+        };
+
+        let key = (registry.clone(), dependency.name.clone());
+        if !self.cache.contains_key(&key) {
+            let entries = try!(Registry::open(registry, &self.base, &self.config).entries(&dependency.name));
+            self.cache.insert(key.clone(), entries);
+        }
+
+        Ok(self.cache[&key].iter().map(|entry| Summary {
+            name: entry.name.clone(),
+            version: entry.version.clone(),
+            reference: None,
+            dependencies: entry.dependencies.clone()
+        }).collect())
+    }
+}
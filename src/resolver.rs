@@ -0,0 +1,437 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Picks a consistent set of versions for Athena and every plugin the game depends on.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+use std::rc::Rc;
+use semver::{Version, VersionReq};
+
+use config::Config;
+use dependency::{Dependency, Source};
+use project::ZeusProjectError;
+use registry::RegistryIndex;
+use vcs::VcsBackend;
+
+// ### Inputs ###
+
+/// One published version of a package, along with what that version requires in turn.
+#[derive(Debug, Clone)]
+pub struct Summary {
+    pub name: String,
+    pub version: Version,
+    /// What to check out to get this version, the tag for git sources.
+    pub reference: Option<String>,
+    pub dependencies: Vec<Dependency>
+}
+
+/// Knows which versions of a package exist.
+pub trait PackageIndex {
+    fn summaries(&mut self, dependency: &Dependency) -> Result<Vec<Summary>, ZeusProjectError>;
+}
+
+/// Finds versions in the tags of a git repository, `v1.2.0` and `1.2.0` style tags both count.
+/// Tags don't say anything about dependencies so these versions never have any.
+pub struct GitTagIndex {
+    vcs: Rc<VcsBackend>,
+    cache: HashMap<String, Vec<Summary>>
+}
+
+impl GitTagIndex {
+    pub fn new(vcs: Rc<VcsBackend>) -> GitTagIndex {
+        GitTagIndex { vcs: vcs, cache: HashMap::new() }
+    }
+}
+
+impl PackageIndex for GitTagIndex {
+    fn summaries(&mut self, dependency: &Dependency) -> Result<Vec<Summary>, ZeusProjectError> {
+        let url = match dependency.source {
+            Source::Git { ref url, .. } => url,
+            _ => return Ok(Vec::new())
+        };
+
+        if !self.cache.contains_key(url) {
+            let summaries = try!(self.vcs.remote_tags(url)).into_iter()
+                .filter_map(|tag| {
+                    let version = Version::parse(tag.trim_left_matches('v')).ok();
+                    version.map(|version| Summary {
+                        name: dependency.name.clone(),
+                        version: version,
+                        reference: Some(tag),
+                        dependencies: Vec::new()
+                    })
+                })
+                .collect();
+            self.cache.insert(url.clone(), summaries);
+        }
+
+        Ok(self.cache[url].clone())
+    }
+}
+
+/// Looks versions up wherever a dependency comes from, git tags or a zeus registry.
+pub struct SourceIndex {
+    git: GitTagIndex,
+    registry: RegistryIndex
+}
+
+impl SourceIndex {
+    /// Registry directories are relative to `base`, usually the project's directory.
+    pub fn new(base: &Path, vcs: Rc<VcsBackend>, config: &Config) -> SourceIndex {
+        SourceIndex {
+            git: GitTagIndex::new(vcs),
+            registry: RegistryIndex::new(base, config)
+        }
+    }
+}
+
+impl PackageIndex for SourceIndex {
+    fn summaries(&mut self, dependency: &Dependency) -> Result<Vec<Summary>, ZeusProjectError> {
+        match dependency.source {
+            Source::ZeusRegistry(_) => self.registry.summaries(dependency),
+            _ => self.git.summaries(dependency)
+        }
+    }
+}
+
+
+// ### Output ###
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedPackage {
+    pub name: String,
+    pub source: Source,
+    /// None for packages pinned to a branch or a path, those have no version to speak of.
+    pub version: Option<Version>,
+    /// The branch or tag to check out for git sources.
+    pub reference: Option<String>,
+    /// The requirement cargo picks a crates.io crate's version with, zeus never picks those.
+    pub requirement: Option<String>,
+    /// The commit git sources were checked out at the first time they were fetched. Branches
+    /// move on, setting up again checks out this commit.
+    pub commit: Option<String>,
+    /// Names of the packages this one depends on.
+    pub dependencies: Vec<String>,
+    /// Hash of the package's sources, recorded the first time they're fetched.
+    pub checksum: Option<String>
+}
+
+/// The complete set of packages a project builds with, sorted by name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolve {
+    /// Names of the packages the project itself depends on.
+    pub roots: Vec<String>,
+    pub packages: Vec<ResolvedPackage>
+}
+
+impl Resolve {
+    pub fn package(&self, name: &str) -> Option<&ResolvedPackage> {
+        self.packages.iter().find(|p| p.name == name)
+    }
+}
+
+
+// ### Resolving ###
+
+/// A requirement along with who made it, to explain conflicts.
+#[derive(Debug, Clone)]
+struct Requirement {
+    dependency: Dependency,
+    required_by: String
+}
+
+#[derive(Debug, Clone)]
+struct State {
+    activated: BTreeMap<String, ResolvedPackage>,
+    seen: Vec<Requirement>
+}
+
+/// Resolves the project's requirements, the game's own name is used in explanations.
+pub fn resolve<I: PackageIndex>(game: &str, dependencies: &[Dependency], index: &mut I)
+    -> Result<Resolve, ZeusProjectError>
+{
+    let pending: Vec<Requirement> = dependencies.iter()
+        .map(|d| Requirement { dependency: d.clone(), required_by: String::from(game) })
+        .collect();
+    let state = State { activated: BTreeMap::new(), seen: Vec::new() };
+
+    match activate(state, pending, index) {
+        Ok(state) => Ok(Resolve {
+            roots: dependencies.iter().map(|d| d.name.clone()).collect(),
+            packages: state.activated.into_iter().map(|(_, package)| package).collect()
+        }),
+        Err(Failure::Conflict(explanation)) => Err(ZeusProjectError::ResolutionFailed(explanation)),
+        Err(Failure::Error(e)) => Err(e)
+    }
+}
+
+enum Failure {
+    Conflict(String),
+    Error(ZeusProjectError)
+}
+
+// Depth first with backtracking, newest versions are tried first
+fn activate<I: PackageIndex>(mut state: State, mut pending: Vec<Requirement>, index: &mut I) -> Result<State, Failure> {
+    if pending.is_empty() { return Ok(state); }
+
+    let requirement = pending.remove(0);
+    state.seen.push(requirement.clone());
+    let dependency = &requirement.dependency;
+    let wanted = dependency.requirement();
+
+    // Already picked, it just has to agree with this requirement
+    if let Some(existing) = state.activated.get(&dependency.name).cloned() {
+        let agrees = same_source(&existing.source, &dependency.source) && match (&wanted, &existing.version) {
+            (&Some(ref wanted), &Some(ref version)) => wanted.matches(version),
+            (&None, _) => existing.reference == pinned_reference(dependency),
+            // Cargo picks these versions itself, zeus can only hand it a single requirement
+            (&Some(_), &None) => existing.requirement == dependency.version
+        };
+
+        if agrees {
+            return activate(state, pending, index);
+        } else {
+            return Err(Failure::Conflict(explain(&state, &dependency.name, None)));
+        }
+    }
+
+    // Pinned to a branch, a path or left to cargo, nothing to pick
+    let wanted = match wanted {
+        Some(ref wanted) if is_versioned(&dependency.source) => wanted.clone(),
+        _ => {
+            state.activated.insert(dependency.name.clone(), ResolvedPackage {
+                name: dependency.name.clone(),
+                source: dependency.source.clone(),
+                version: None,
+                reference: pinned_reference(dependency),
+                requirement: match dependency.source {
+                    Source::Registry => dependency.version.clone(),
+                    _ => None
+                },
+                commit: None,
+                dependencies: Vec::new(),
+                checksum: None
+            });
+            return activate(state, pending, index);
+        }
+    };
+
+    let available = try!(index.summaries(dependency).map_err(Failure::Error));
+    let mut candidates: Vec<&Summary> = available.iter().filter(|s| wanted.matches(&s.version)).collect();
+    candidates.sort_by(|a, b| b.version.cmp(&a.version));
+
+    if candidates.is_empty() {
+        return Err(Failure::Conflict(explain(&state, &dependency.name, Some(&available))));
+    }
+
+    let mut last_conflict = None;
+    for candidate in candidates {
+        let mut next = state.clone();
+        next.activated.insert(dependency.name.clone(), ResolvedPackage {
+            name: dependency.name.clone(),
+            source: dependency.source.clone(),
+            version: Some(candidate.version.clone()),
+            reference: candidate.reference.clone(),
+            requirement: None,
+            commit: None,
+            dependencies: candidate.dependencies.iter().map(|d| d.name.clone()).collect(),
+            checksum: None
+        });
+
+        let mut next_pending = pending.clone();
+        let required_by = format!("{} {}", dependency.name, candidate.version);
+        next_pending.extend(candidate.dependencies.iter().map(|d| Requirement {
+            dependency: d.clone(),
+            required_by: required_by.clone()
+        }));
+
+        match activate(next, next_pending, index) {
+            Ok(state) => return Ok(state),
+            Err(Failure::Conflict(explanation)) => last_conflict = Some(explanation),
+            Err(error) => return Err(error)
+        }
+    }
+
+    Err(Failure::Conflict(last_conflict.expect("at least one candidate was tried")))
+}
+
+// Spells out every requirement on a package that led to the conflict
+fn explain(state: &State, name: &str, available: Option<&[Summary]>) -> String {
+    let mut lines = vec![format!("no version of `{}` satisfies every requirement on it:", name)];
+
+    for requirement in state.seen.iter().filter(|r| r.dependency.name == name) {
+        let wanted = match (&requirement.dependency.version, pinned_reference(&requirement.dependency)) {
+            (&Some(ref version), _) => version.clone(),
+            (&None, Some(reference)) => format!("`{}`", reference),
+            (&None, None) => String::from("any version")
+        };
+        lines.push(format!("  - {} requires {} {} from {}", requirement.required_by, name, wanted, requirement.dependency.source));
+    }
+
+    if let Some(picked) = state.activated.get(name) {
+        match picked.version {
+            Some(ref version) => lines.push(format!("  `{}` {} was already picked", name, version)),
+            None => lines.push(format!("  `{}` from {} was already picked", name, picked.source))
+        }
+    }
+
+    if let Some(available) = available {
+        let mut versions: Vec<&Version> = available.iter().map(|s| &s.version).collect();
+        versions.sort();
+        if versions.is_empty() {
+            lines.push(String::from("  no versions are available"));
+        } else {
+            let versions: Vec<String> = versions.iter().map(|v| v.to_string()).collect();
+            lines.push(format!("  available versions: {}", versions.join(", ")));
+        }
+    }
+
+    lines.join("\n")
+}
+
+fn pinned_reference(dependency: &Dependency) -> Option<String> {
+    match dependency.source {
+        Source::Git { ref reference, .. } => reference.clone(),
+        _ => None
+    }
+}
+
+// Sources zeus picks versions from, cargo picks its own registry's
+fn is_versioned(source: &Source) -> bool {
+    match *source { Source::Git { .. } | Source::ZeusRegistry(_) => true, _ => false }
+}
+
+// The pinned branch is compared separately, only the location matters here
+fn same_source(a: &Source, b: &Source) -> bool {
+    match (a, b) {
+        (&Source::Git { url: ref a, .. }, &Source::Git { url: ref b, .. }) => a == b,
+        (a, b) => a == b
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use semver::Version;
+
+    use dependency::{Dependency, FetchOptions, Source};
+    use project::ZeusProjectError;
+    use super::{resolve, PackageIndex, Resolve, Summary};
+
+    struct TestIndex(Vec<Summary>);
+
+    impl PackageIndex for TestIndex {
+        fn summaries(&mut self, dependency: &Dependency) -> Result<Vec<Summary>, ZeusProjectError> {
+            Ok(self.0.iter().filter(|summary| summary.name == dependency.name).cloned().collect())
+        }
+    }
+
+    fn dependency(name: &str, source: Source, version: &str) -> Dependency {
+        Dependency {
+            name: String::from(name),
+            source: source,
+            version: Some(String::from(version)),
+            fetch: FetchOptions::default()
+        }
+    }
+
+    fn plugin(name: &str, version: &str) -> Dependency {
+        dependency(name, Source::ZeusRegistry(String::from("plugins")), version)
+    }
+
+    fn krate(name: &str, version: &str) -> Dependency {
+        dependency(name, Source::Registry, version)
+    }
+
+    fn summary(name: &str, version: &str, dependencies: Vec<Dependency>) -> Summary {
+        Summary {
+            name: String::from(name),
+            version: Version::parse(version).unwrap(),
+            reference: None,
+            dependencies: dependencies
+        }
+    }
+
+    fn picked(resolve: &Resolve, name: &str) -> String {
+        resolve.package(name).and_then(|package| package.version.as_ref()).unwrap().to_string()
+    }
+
+    fn conflict(result: Result<Resolve, ZeusProjectError>) -> String {
+        match result {
+            Err(ZeusProjectError::ResolutionFailed(explanation)) => explanation,
+            other => panic!("expected a conflict, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn crates_required_twice_alike_are_shared() {
+        let mut index = TestIndex(vec![summary("physics", "1.0.0", vec![krate("rand", "0.3")])]);
+        let resolve = resolve("game", &[krate("rand", "0.3"), plugin("physics", "^1.0")], &mut index).unwrap();
+
+        let rand = resolve.package("rand").unwrap();
+        assert_eq!(rand.requirement, Some(String::from("0.3")));
+        assert_eq!(rand.version, None);
+        assert_eq!(picked(&resolve, "physics"), "1.0.0");
+    }
+
+    #[test]
+    fn crates_required_twice_differently_conflict() {
+        let mut index = TestIndex(vec![summary("physics", "1.0.0", vec![krate("rand", "0.2")])]);
+        let explanation = conflict(resolve("game", &[krate("rand", "0.3"), plugin("physics", "^1.0")], &mut index));
+
+        assert!(explanation.contains("  - game requires rand 0.3 from registry"));
+        assert!(explanation.contains("  - physics 1.0.0 requires rand 0.2 from registry"));
+    }
+
+    #[test]
+    fn newest_matching_versions_are_picked() {
+        let mut index = TestIndex(vec![
+            summary("physics", "1.0.0", Vec::new()),
+            summary("physics", "1.4.2", Vec::new()),
+            summary("physics", "2.0.0", Vec::new())
+        ]);
+        let resolve = resolve("game", &[plugin("physics", "^1.0")], &mut index).unwrap();
+
+        assert_eq!(picked(&resolve, "physics"), "1.4.2");
+    }
+
+    #[test]
+    fn conflicts_backtrack_to_older_versions() {
+        let mut index = TestIndex(vec![
+            summary("physics", "1.0.0", vec![plugin("math", "^1.0")]),
+            summary("physics", "1.1.0", vec![plugin("math", "^2.0")]),
+            summary("math", "1.2.0", Vec::new()),
+            summary("math", "2.0.0", Vec::new())
+        ]);
+        let resolve = resolve("game", &[plugin("physics", "^1.0"), plugin("math", "^1.0")], &mut index).unwrap();
+
+        assert_eq!(picked(&resolve, "physics"), "1.0.0");
+        assert_eq!(picked(&resolve, "math"), "1.2.0");
+        assert_eq!(resolve.package("physics").unwrap().dependencies, vec![String::from("math")]);
+    }
+
+    #[test]
+    fn conflicts_explain_every_requirement() {
+        let mut index = TestIndex(vec![
+            summary("math", "1.0.0", Vec::new()),
+            summary("math", "1.1.0", Vec::new())
+        ]);
+        let explanation = conflict(resolve("game", &[plugin("math", "^2.0")], &mut index));
+
+        assert_eq!(explanation, "no version of `math` satisfies every requirement on it:\n  \
+                                 - game requires math ^2.0 from registry plugins\n  \
+                                 available versions: 1.0.0, 1.1.0");
+    }
+}
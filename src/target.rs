@@ -0,0 +1,196 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Platforms the game is cross-compiled for, `[target.<triple>]` in Zeus.toml:
+//!
+//! ```toml
+//! [target.arm-linux-androideabi]
+//! linker = "arm-linux-androideabi-gcc"
+//! features = ["touch-controls"]
+//! assets = "mobile"
+//! ```
+//!
+//! Building for a triple without a table works too, it just gets nothing extra. Triples with a
+//! dot in them need quoting, like `[target."thumbv8m.main-none-eabi"]`.
+
+use std::process::{Command, Stdio};
+use toml::{Table, Value};
+
+use project::ZeusProjectError;
+use toolchain::Toolchain;
+
+/// Keys a `[target.<triple>]` table may use.
+pub static TARGET_KEYS: &'static [&'static str] = &["linker", "features", "assets"];
+
+static RUSTUP_PATH: &'static str = "rustup";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Target {
+    /// Like `x86_64-pc-windows-gnu`.
+    pub triple: String,
+    /// The linker cargo links with, the platform's default when not set.
+    pub linker: Option<String>,
+    /// Features from `[features]` to turn on when building for it.
+    pub features: Vec<String>,
+    /// The variant of the game's assets to use, like `mobile`.
+    pub assets: Option<String>
+}
+
+impl Target {
+    /// Reads a `[target.<triple>]` table.
+    pub fn parse(triple: &str, table: &Table) -> Result<Target, String> {
+        try!(check_triple(triple));
+
+        let get_str = |key: &str| match table.get(key) {
+            Some(&Value::String(ref value)) if !value.trim().is_empty() => Ok(Some(value.trim().to_string())),
+            Some(_) => Err(format!("`{}` must be a non-empty string", key)),
+            None => Ok(None)
+        };
+
+        let features = match table.get("features") {
+            Some(&Value::Array(ref features)) => {
+                let names: Vec<String> = features.iter().filter_map(|f| f.as_str()).map(String::from).collect();
+                if names.len() != features.len() {
+                    return Err(String::from("`features` must be a list of feature names"));
+                }
+                names
+            },
+            Some(_) => return Err(String::from("`features` must be a list of feature names")),
+            None => Vec::new()
+        };
+
+        Ok(Target {
+            triple: String::from(triple),
+            linker: try!(get_str("linker")),
+            features: features,
+            assets: try!(get_str("assets"))
+        })
+    }
+
+    /// The target to build for a triple with, from its table if it has one.
+    pub fn resolve(manifest: &Value, triple: &str) -> Result<Target, ZeusProjectError> {
+        let invalid = |reason: String| ZeusProjectError::InvalidTarget { triple: String::from(triple), reason: reason };
+
+        match manifest.lookup("target").and_then(|targets| targets.as_table()).and_then(|targets| targets.get(triple)) {
+            Some(&Value::Table(ref table)) => Target::parse(triple, table).map_err(&invalid),
+            Some(_) => Err(invalid(format!("`target.{}` must be a table", triple))),
+            None => {
+                try!(check_triple(triple).map_err(&invalid));
+                Ok(Target { triple: String::from(triple), linker: None, features: Vec::new(), assets: None })
+            }
+        }
+    }
+
+    /// The triple, and the asset variant when the target picks one.
+    pub fn vars(&self) -> Vec<(String, String)> {
+        let mut vars = vec![(String::from("ATHENA_TARGET"), self.triple.clone())];
+        if let Some(ref assets) = self.assets {
+            vars.push((String::from("ATHENA_ASSET_VARIANT"), assets.clone()));
+        }
+        vars
+    }
+}
+
+/// Every target with a table in the manifest by triple, along with the target or what's wrong
+/// with its table.
+pub fn targets(manifest: &Value) -> Vec<(String, Result<Target, String>)> {
+    match manifest.lookup("target").and_then(|targets| targets.as_table()) {
+        Some(targets) => targets.iter()
+            .map(|(triple, value)| (triple.clone(), match value.as_table() {
+                Some(table) => Target::parse(triple, table),
+                None => Err(format!("`target.{}` must be a table", triple))
+            }))
+            .collect(),
+        None => Vec::new()
+    }
+}
+
+/// The triples the toolchain has a standard library for, None without rustup to ask.
+pub fn installed_std(toolchain: Option<&Toolchain>) -> Option<Vec<String>> {
+    let mut command = Command::new(RUSTUP_PATH);
+    command.arg("target").arg("list").arg("--installed");
+    if let Some(toolchain) = toolchain {
+        command.arg("--toolchain").arg(toolchain.name());
+    }
+
+    match command.stdin(Stdio::null()).stderr(Stdio::null()).output() {
+        Ok(ref output) if output.status.success() => Some(String::from_utf8_lossy(&output.stdout)
+            .lines().map(|line| line.trim()).filter(|line| !line.is_empty()).map(String::from).collect()),
+        _ => None
+    }
+}
+
+/// Makes sure the standard library for a target is installed, as far as rustup can tell.
+pub fn ensure_std(target: &Target, toolchain: Option<&Toolchain>) -> Result<(), ZeusProjectError> {
+    let installed = match installed_std(toolchain) {
+        Some(installed) => installed,
+        // Without rustup cargo will be the one to complain
+        None => return Ok(())
+    };
+
+    if installed.contains(&target.triple) {
+        return Ok(());
+    }
+
+    let toolchain_arg = toolchain.map(|toolchain| format!(" --toolchain {}", toolchain.name())).unwrap_or(String::new());
+    Err(ZeusProjectError::ToolNotFound {
+        tool: format!("the standard library for {}", target.triple),
+        hint: format!("Run `{} target add {}{}` to install it.", RUSTUP_PATH, target.triple, toolchain_arg)
+    })
+}
+
+// Triples are at least an architecture and a system separated by dashes, like `wasm32-wasi`.
+// Architectures may have dots in them, like `thumbv8m.main-none-eabi`
+fn check_triple(triple: &str) -> Result<(), String> {
+    let parts: Vec<&str> = triple.split('-').collect();
+    if parts.len() < 2 || parts.iter().any(|part| part.is_empty())
+        || !triple.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '.') {
+        return Err(format!("`{}` is not a target triple like `x86_64-unknown-linux-gnu`", triple));
+    }
+    Ok(())
+}
+
+
+#[cfg(test)]
+mod tests {
+    use toml::Table;
+
+    use super::Target;
+
+    fn accepts(triple: &str) -> bool {
+        Target::parse(triple, &Table::new()).is_ok()
+    }
+
+    #[test]
+    fn usual_triples_are_accepted() {
+        assert!(accepts("x86_64-unknown-linux-gnu"));
+        assert!(accepts("arm-linux-androideabi"));
+        assert!(accepts("x86_64-pc-windows-msvc"));
+    }
+
+    #[test]
+    fn two_part_and_dotted_triples_are_accepted() {
+        assert!(accepts("wasm32-wasi"));
+        assert!(accepts("thumbv8m.main-none-eabi"));
+    }
+
+    #[test]
+    fn malformed_triples_are_rejected() {
+        assert!(!accepts("linux"));
+        assert!(!accepts("x86_64--linux-gnu"));
+        assert!(!accepts("-linux-gnu"));
+        assert!(!accepts("x86_64-linux gnu"));
+        assert!(!accepts("x86_64-linux\"-gnu"));
+    }
+}
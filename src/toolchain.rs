@@ -0,0 +1,186 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The rust toolchain a project builds with, pinned in Zeus.toml:
+//!
+//! ```toml
+//! [toolchain]
+//! channel = "nightly"
+//! date = "2015-08-05"
+//! ```
+//!
+//! Toolchains are installed and picked through rustup. Without rustup zeus can only check the
+//! compiler on the PATH is from the right channel.
+
+use std::path::Path;
+use std::process::{Command, Stdio};
+use toml::{Table, Value};
+
+use compat;
+use project::ZeusProjectError;
+
+/// Keys the `[toolchain]` table may use.
+pub static TOOLCHAIN_KEYS: &'static [&'static str] = &["channel", "date"];
+
+/// Channels a toolchain may follow, instead of naming a release like `1.3.0`.
+pub static CHANNELS: &'static [&'static str] = &["stable", "beta", "nightly"];
+
+/// Picks the toolchain for rustup's proxies, like `cargo` and `rustc`.
+pub static TOOLCHAIN_VAR: &'static str = "RUSTUP_TOOLCHAIN";
+
+static RUSTUP_PATH: &'static str = "rustup";
+static RUSTC_PATH: &'static str = "rustc";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Toolchain {
+    /// A channel or a release.
+    pub channel: String,
+    /// The day of the channel's build to use, like `2015-08-05`.
+    pub date: Option<String>
+}
+
+impl Toolchain {
+    /// Reads the `[toolchain]` table.
+    pub fn parse(table: &Table) -> Result<Toolchain, String> {
+        let channel = match table.get("channel") {
+            Some(&Value::String(ref channel)) => channel.trim(),
+            Some(_) => return Err(String::from("`channel` must be a string")),
+            None => return Err(String::from("missing `channel`"))
+        };
+        let date = match table.get("date") {
+            Some(&Value::String(ref date)) => Some(date.trim()),
+            Some(_) => return Err(String::from("`date` must be a string")),
+            None => None
+        };
+
+        let is_channel = CHANNELS.contains(&channel);
+        if !is_channel && compat::parse_tool_version(channel).map_or(true, |v| v.to_string() != channel) {
+            return Err(format!("`{}` is not one of the channels {} or a release like `1.3.0`", channel, CHANNELS.join(", ")));
+        }
+
+        if let Some(date) = date {
+            if !is_channel {
+                return Err(String::from("`date` can only be used with a channel"));
+            }
+            if !is_date(date) {
+                return Err(format!("`{}` is not a date like `2015-08-05`", date));
+            }
+        }
+
+        Ok(Toolchain { channel: String::from(channel), date: date.map(String::from) })
+    }
+
+    /// The toolchain a manifest pins, if any, or why the `[toolchain]` table doesn't pin one.
+    pub fn from_manifest(manifest: &Value) -> Result<Option<Toolchain>, String> {
+        match manifest.lookup("toolchain") {
+            Some(&Value::Table(ref table)) => Toolchain::parse(table).map(Some),
+            Some(_) => Err(String::from("`toolchain` must be a table")),
+            None => Ok(None)
+        }
+    }
+
+    /// How rustup calls it, like `nightly-2015-08-05`.
+    pub fn name(&self) -> String {
+        match self.date {
+            Some(ref date) => format!("{}-{}", self.channel, date),
+            None => self.channel.clone()
+        }
+    }
+
+    /// Whether a `rustc --version` comes from this toolchain, as far as it tells.
+    pub fn matches_rustc(&self, version: &str) -> bool {
+        match &self.channel[..] {
+            "stable" => !version.contains("-beta") && !version.contains("-nightly") && !version.contains("-dev"),
+            "beta" => version.contains("-beta"),
+            "nightly" => version.contains("-nightly"),
+            release => compat::parse_tool_version(version).map_or(false, |v| v.to_string() == release)
+        }
+    }
+}
+
+/// Whether rustup is around to install and pick toolchains.
+pub fn has_rustup() -> bool {
+    Command::new(RUSTUP_PATH).arg("--version")
+        .stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// What `rustc --version` says for the toolchain, or None if it isn't installed. Without rustup
+/// that's the compiler on the PATH, as long as it's from the right channel.
+pub fn installed_version(toolchain: &Toolchain) -> Result<Option<String>, ZeusProjectError> {
+    let mut command = if has_rustup() {
+        let mut command = Command::new(RUSTUP_PATH);
+        command.arg("run").arg(toolchain.name()).arg(RUSTC_PATH);
+        command
+    } else {
+        Command::new(RUSTC_PATH)
+    };
+
+    let output = try!(command.arg("--version").stdin(Stdio::null()).output()
+        .map_err(|e| ZeusProjectError::io(Path::new(RUSTC_PATH), e)));
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+    if output.status.success() && toolchain.matches_rustc(&version) {
+        Ok(Some(version))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Makes sure the toolchain is there, installing it through rustup if `install` is set.
+pub fn ensure(toolchain: &Toolchain, install: bool) -> Result<(), ZeusProjectError> {
+    if try!(installed_version(toolchain)).is_some() {
+        return Ok(());
+    }
+
+    if !has_rustup() {
+        return Err(not_installed(toolchain, String::from(
+            "Install rustup from https://www.rust-lang.org so zeus can install it, or put a matching rustc on the PATH.")));
+    }
+    if !install {
+        return Err(not_installed(toolchain, format!(
+            "Run `zeus setup` or `{} toolchain install {}` to install it.", RUSTUP_PATH, toolchain.name())));
+    }
+
+    // Rustup's own progress goes straight to the user, a toolchain is a big download
+    let status = try!(Command::new(RUSTUP_PATH)
+        .arg("toolchain").arg("install").arg(toolchain.name())
+        .stdin(Stdio::null())
+        .status()
+        .map_err(|e| ZeusProjectError::io(Path::new(RUSTUP_PATH), e)));
+
+    if status.success() {
+        return Ok(());
+    } else {
+        return Err(ZeusProjectError::CommandFailed {
+            command: format!("{} toolchain install {}", RUSTUP_PATH, toolchain.name()),
+            output: String::from("See rustup's output above.")
+        });
+    }
+}
+
+fn not_installed(toolchain: &Toolchain, hint: String) -> ZeusProjectError {
+    ZeusProjectError::ToolNotFound {
+        tool: format!("the {} rust toolchain this project pins", toolchain.name()),
+        hint: hint
+    }
+}
+
+fn is_date(date: &str) -> bool {
+    let parts: Vec<&str> = date.split('-').collect();
+    parts.len() == 3
+        && parts.iter().zip([4, 2, 2].iter()).all(|(part, &len)| part.len() == len && part.chars().all(|c| c.is_digit(10)))
+}
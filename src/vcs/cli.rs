@@ -0,0 +1,425 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `git` command line tool, what zeus has always used.
+
+use std::env;
+use std::env::consts::EXE_SUFFIX;
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+
+use auth;
+use auth::HostAuth;
+use config;
+use config::{Config, Origin};
+use dependency::FetchOptions;
+use progress::{Phase, Progress, ProgressSink, Unit};
+use project::ZeusProjectError;
+use vcs::VcsBackend;
+
+/// Points at the git executable to use, it's how the `git.path` setting is overridden.
+pub static GIT_VAR: &'static str = "ZEUS_GIT";
+
+// Hand the username and token to the credential helper zeus sets up for tokens
+static USERNAME_VAR: &'static str = "ZEUS_GIT_USERNAME";
+static TOKEN_VAR: &'static str = "ZEUS_GIT_TOKEN";
+
+// Where git usually ends up when it isn't on the PATH
+#[cfg(windows)]
+static PLATFORM_GITS: &'static [&'static str] = &[
+    r#"C:\Program Files\Git\cmd\git.exe"#,
+    r#"C:\Program Files\Git\bin\git.exe"#,
+    r#"C:\Program Files (x86)\Git\cmd\git.exe"#,
+    r#"C:\Program Files (x86)\Git\bin\git.exe"#
+];
+
+#[cfg(not(windows))]
+static PLATFORM_GITS: &'static [&'static str] = &["/usr/bin/git", "/usr/local/bin/git", "/opt/local/bin/git"];
+
+/// How the git executable was found.
+#[derive(Debug, Clone, PartialEq)]
+pub enum GitOrigin {
+    /// The `git.path` setting, from any of the config's layers.
+    Configured(Origin),
+    SearchPath,
+    PlatformDefault
+}
+
+impl Display for GitOrigin {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            GitOrigin::Configured(Origin::Environment(var)) => write!(f, "from {}", var),
+            GitOrigin::Configured(ref origin) => write!(f, "from `git.path` ({})", origin),
+            GitOrigin::SearchPath => write!(f, "found on the PATH"),
+            GitOrigin::PlatformDefault => write!(f, "found in a default install location")
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct CliGit {
+    git: PathBuf,
+    origin: GitOrigin
+}
+
+impl CliGit {
+    /// Uses a specific git executable.
+    pub fn new(git: PathBuf, origin: GitOrigin) -> CliGit {
+        CliGit { git: git, origin: origin }
+    }
+
+    /// Finds git through the `git.path` setting (`ZEUS_GIT` in the environment), then the PATH
+    /// and finally wherever the platform usually installs it.
+    pub fn discover(config: &Config) -> Result<CliGit, ZeusProjectError> {
+        // Explicit choices have to exist, falling back would hide a mistake
+        if let Some(setting) = config.get("git.path") {
+            if let Some(git) = setting.value.as_str() {
+                let git = PathBuf::from(git);
+                if !is_file(&git) {
+                    return Err(ZeusProjectError::ToolNotFound {
+                        tool: String::from("git"),
+                        hint: format!("`git.path` ({}) points at {}, which doesn't exist.", setting.origin, git.display())
+                    });
+                }
+                return Ok(CliGit::new(git, GitOrigin::Configured(setting.origin.clone())));
+            }
+        }
+
+        if let Some(paths) = env::var_os("PATH") {
+            let executable = format!("git{}", EXE_SUFFIX);
+            if let Some(git) = env::split_paths(&paths).map(|dir| dir.join(&executable)).find(|git| is_file(git)) {
+                return Ok(CliGit::new(git, GitOrigin::SearchPath));
+            }
+        }
+
+        if let Some(git) = PLATFORM_GITS.iter().map(PathBuf::from).find(|git| is_file(git)) {
+            return Ok(CliGit::new(git, GitOrigin::PlatformDefault));
+        }
+
+        Err(ZeusProjectError::ToolNotFound {
+            tool: String::from("git"),
+            hint: format!("Install git, or point {} or `git.path` in ~/{} at it.", GIT_VAR, config::USER_CONFIG)
+        })
+    }
+
+    pub fn path(&self) -> &Path { &self.git }
+    pub fn origin(&self) -> &GitOrigin { &self.origin }
+
+    /// What `git --version` says, like `git version 2.5.0`.
+    pub fn version(&self) -> Result<String, ZeusProjectError> {
+        let output = try!(self.run(&["--version"], None));
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Makes sure git-lfs is installed, it's a separate download from git itself.
+    pub fn check_lfs(&self) -> Result<(), ZeusProjectError> {
+        if self.run(&["lfs", "version"], None).is_ok() {
+            return Ok(());
+        } else {
+            return Err(ZeusProjectError::ToolNotFound {
+                tool: String::from("git-lfs"),
+                hint: String::from("It's needed for dependencies with `lfs = true`, install it from https://git-lfs.github.com")
+            });
+        }
+    }
+
+    // The url a checkout was cloned from, what it fetches from
+    fn origin_url(&self, path: &Path) -> Result<String, ZeusProjectError> {
+        let output = try!(self.run(&["config", "--get", "remote.origin.url"], Some(path)));
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    // Pulls the LFS objects of whatever is checked out, and of its submodules when they were cloned too
+    fn pull_lfs(&self, url: &str, path: &Path, options: &FetchOptions) -> Result<(), ZeusProjectError> {
+        try!(self.run_remote(url, &["lfs", "pull"], Some(path), Access::default()));
+        if options.submodules {
+            try!(self.run(&["submodule", "foreach", "--recursive", "git lfs pull"], Some(path)));
+        }
+        Ok(())
+    }
+
+    fn run(&self, args: &[&str], path: Option<&Path>) -> Result<Output, ZeusProjectError> {
+        self.run_with(args, path, &Access::default())
+    }
+
+    // Runs a command that talks to `url`, turning a failed login into an error that says so
+    fn run_remote(&self, url: &str, args: &[&str], path: Option<&Path>, mut access: Access)
+        -> Result<Output, ZeusProjectError>
+    {
+        try!(access.authenticate(url));
+
+        let result = self.run_with(args, path, &access);
+        if let Err(ZeusProjectError::CommandFailed { ref output, .. }) = result {
+            if let (true, Some(host)) = (auth::is_auth_failure(output), auth::host_of(url)) {
+                let hint = auth::auth_hint(&host);
+                return Err(ZeusProjectError::AuthenticationFailed { host: host, hint: hint });
+            }
+        }
+
+        result
+    }
+
+    fn run_with(&self, args: &[&str], path: Option<&Path>, access: &Access) -> Result<Output, ZeusProjectError> {
+        let mut command = Command::new(&self.git);
+        for setting in access.config.iter() {
+            command.arg("-c").arg(setting);
+        }
+        command.args(args);
+        if let Some(path) = path {
+            command.current_dir(path);
+        }
+        for &(ref key, ref value) in access.vars.iter() {
+            command.env(key, value);
+        }
+
+        let output = try!(match access.progress {
+            Some(progress) => run_with_progress(command, progress),
+            None => command.stdin(Stdio::null()).output()
+        }.map_err(|e| ZeusProjectError::io(&self.git, e)));
+
+        if output.status.success() {
+            return Ok(output);
+        } else {
+            // Urls can carry tokens, none of them belong in an error message
+            let location = path.map(|path| format!(" (in {})", path.display())).unwrap_or(String::new());
+            let command = format!("{} {}{}", self.git.display(), args.join(" "), location);
+            return Err(ZeusProjectError::CommandFailed {
+                command: auth::redact(&command, &access.secrets),
+                output: auth::redact(&String::from_utf8_lossy(&output.stderr), &access.secrets)
+            });
+        }
+    }
+}
+
+// GIT_SSH_COMMAND goes through the shell, single quotes keep paths with spaces or quotes whole
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace("'", "'\\''"))
+}
+
+// What a command needs on top of its arguments: `-c` settings, environment variables, the
+// secrets to keep out of error messages and where its progress goes
+#[derive(Debug, Default)]
+struct Access<'a> {
+    config: Vec<String>,
+    vars: Vec<(String, String)>,
+    secrets: Vec<String>,
+    progress: Option<&'a ProgressSink>
+}
+
+impl<'a> Access<'a> {
+    fn with_progress(progress: &'a ProgressSink) -> Access<'a> {
+        Access { progress: Some(progress), ..Access::default() }
+    }
+
+    // Sets up the credentials configured for the url's host, and makes sure nothing prompts
+    // for them when nobody is around to answer
+    fn authenticate(&mut self, url: &str) -> Result<(), ZeusProjectError> {
+        let host_auth = try!(auth::host_auth(url)).unwrap_or(HostAuth::default());
+        let non_interactive = auth::non_interactive();
+
+        // Without a key of its own ssh sticks to the agent and its usual keys
+        let mut ssh = vec![String::from("ssh")];
+        if let Some(ref key) = host_auth.ssh_key {
+            ssh.push(format!("-i {} -o IdentitiesOnly=yes", shell_quote(&key.to_string_lossy())));
+        }
+        if non_interactive {
+            ssh.push(String::from("-o BatchMode=yes"));
+            self.vars.push((String::from("GIT_TERMINAL_PROMPT"), String::from("0")));
+        }
+        if ssh.len() > 1 {
+            self.vars.push((String::from("GIT_SSH_COMMAND"), ssh.join(" ")));
+        }
+
+        // Helpers add up, an empty one first drops the user's own so a keychain can't answer instead
+        let token = host_auth.token();
+        if host_auth.credential_helper.is_some() || token.is_some() {
+            self.config.push(String::from("credential.helper="));
+        }
+        if let Some(token) = token {
+            // The helper reads the username and token from the environment, so neither shows up
+            // in a command line or gets parsed by the shell
+            let username = host_auth.username.clone().unwrap_or(String::from("zeus"));
+            self.vars.push((String::from(USERNAME_VAR), username));
+            self.vars.push((String::from(TOKEN_VAR), token.clone()));
+            self.config.push(format!("credential.helper=!f() {{ echo \"username=${}\"; echo \"password=${}\"; }}; f",
+                USERNAME_VAR, TOKEN_VAR));
+            self.secrets.push(token);
+        } else if let Some(ref username) = host_auth.username {
+            self.config.push(format!("credential.username={}", username));
+        }
+        if let Some(ref helper) = host_auth.credential_helper {
+            self.config.push(format!("credential.helper={}", helper));
+        }
+
+        Ok(())
+    }
+}
+
+impl VcsBackend for CliGit {
+    fn name(&self) -> &'static str { "cli" }
+
+    /// Shallow clones a branch or tag, along with its submodules and LFS objects when asked to.
+    fn clone(&self, url: &str, path: &Path, reference: &str, options: &FetchOptions, progress: &ProgressSink)
+        -> Result<(), ZeusProjectError>
+    {
+        // Better to find out now than halfway through a big checkout
+        if options.lfs {
+            try!(self.check_lfs());
+        }
+
+        let path_str = path.to_string_lossy();
+        let mut args = vec!["clone", "--progress", "--branch", reference, "--depth", "1"];
+        if options.submodules {
+            args.push("--recursive");
+        }
+        args.push(url);
+        args.push(&path_str[..]);
+
+        // LFS objects are pulled explicitly below, whether git-lfs is set up globally or not
+        let mut access = Access::with_progress(progress);
+        access.vars.push((String::from("GIT_LFS_SKIP_SMUDGE"), String::from("1")));
+        try!(self.run_remote(url, &args, None, access));
+
+        if options.lfs {
+            progress.report(&Progress::new(Phase::Fetch, "", "Pulling LFS objects"));
+            try!(self.pull_lfs(url, path, options));
+        }
+
+        Ok(())
+    }
+
+    fn fetch(&self, path: &Path, reference: &str, progress: &ProgressSink) -> Result<(), ZeusProjectError> {
+        let url = try!(self.origin_url(path));
+        let args = ["fetch", "--progress", "--depth", "1", "origin", reference];
+        self.run_remote(&url, &args, Some(path), Access::with_progress(progress)).map(|_| ())
+    }
+
+    fn checkout(&self, path: &Path, revision: &str, options: &FetchOptions) -> Result<(), ZeusProjectError> {
+        try!(self.run(&["checkout", "--quiet", revision], Some(path)));
+
+        // Submodules and LFS objects pulled along with the tip have to follow it to the older commit
+        if fs::metadata(path.join(".gitmodules")).is_ok() {
+            try!(self.run(&["submodule", "update", "--quiet", "--recursive"], Some(path)));
+        }
+        if options.lfs {
+            try!(self.pull_lfs(&try!(self.origin_url(path)), path, options));
+        }
+
+        Ok(())
+    }
+
+    fn rev_parse(&self, path: &Path, revision: &str) -> Result<String, ZeusProjectError> {
+        let output = try!(self.run(&["rev-parse", revision], Some(path)));
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    fn status(&self, path: &Path) -> Result<Vec<String>, ZeusProjectError> {
+        // Lines look like `XY <path>`
+        let output = try!(self.run(&["status", "--porcelain"], Some(path)));
+        Ok(String::from_utf8_lossy(&output.stdout).lines()
+            .filter(|line| line.len() > 3)
+            .map(|line| String::from(&line[3..]))
+            .collect())
+    }
+
+    /// Checks a remote repository has a branch or tag, without cloning it.
+    fn remote_has_ref(&self, url: &str, reference: &str) -> Result<bool, ZeusProjectError> {
+        let output = try!(self.run_remote(url, &["ls-remote", url, reference], None, Access::default()));
+        Ok(!output.stdout.is_empty())
+    }
+
+    /// Lists the tags of a remote repository without cloning it.
+    fn remote_tags(&self, url: &str) -> Result<Vec<String>, ZeusProjectError> {
+        let output = try!(self.run_remote(url, &["ls-remote", "--tags", url], None, Access::default()));
+
+        // Lines look like `<hash>\trefs/tags/<name>`, annotated tags show up twice with a `^{}` suffix
+        let tags = String::from_utf8_lossy(&output.stdout).lines()
+            .filter_map(|line| line.split('\t').nth(1))
+            .filter(|reference| !reference.ends_with("^{}"))
+            .map(|reference| String::from(reference.trim_left_matches("refs/tags/")))
+            .collect();
+
+        Ok(tags)
+    }
+}
+
+// Git rewrites its progress in place with carriage returns, every line of it is reported
+// as it comes in. Stdout is dropped, nothing that reports progress prints anything useful there.
+fn run_with_progress(mut command: Command, progress: &ProgressSink) -> io::Result<Output> {
+    let mut child = try!(command.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::piped()).spawn());
+
+    // Progress lines go to the sink, only the rest is kept so errors aren't buried under them
+    let mut stderr = Vec::new();
+    let mut line = Vec::new();
+    for byte in BufReader::new(child.stderr.take().expect("stderr is piped")).bytes() {
+        let byte = try!(byte);
+
+        if byte == b'\r' || byte == b'\n' {
+            match parse_progress(&String::from_utf8_lossy(&line)) {
+                Some(update) => progress.report(&update),
+                None if !line.is_empty() => {
+                    stderr.extend(line.iter().cloned());
+                    stderr.push(b'\n');
+                },
+                None => {}
+            }
+            line.clear();
+        } else {
+            line.push(byte);
+        }
+    }
+    if parse_progress(&String::from_utf8_lossy(&line)).is_none() {
+        stderr.extend(line.iter().cloned());
+    }
+
+    let status = try!(child.wait());
+    Ok(Output { status: status, stdout: Vec::new(), stderr: stderr })
+}
+
+// Lines look like `Receiving objects:  45% (450/1000), 1.20 MiB | 500.00 KiB/s`
+fn parse_progress(line: &str) -> Option<Progress> {
+    let line = line.trim_left_matches("remote: ");
+    let colon = match line.find(": ") {
+        Some(colon) => colon,
+        None => return None
+    };
+    let (detail, rest) = (&line[..colon], &line[colon + 2..]);
+
+    let open = rest.find('(');
+    let close = rest.find(')');
+    let counts = match (open, close) {
+        (Some(open), Some(close)) if open < close => &rest[open + 1..close],
+        _ => return None
+    };
+
+    let mut numbers = counts.split('/').map(|n| n.trim().parse::<u64>());
+    match (numbers.next(), numbers.next()) {
+        (Some(Ok(current)), Some(Ok(total))) => Some(Progress {
+            current: current,
+            total: Some(total),
+            unit: Unit::Objects,
+            ..Progress::new(Phase::Fetch, "", detail)
+        }),
+        _ => None
+    }
+}
+
+fn is_file(path: &Path) -> bool {
+    fs::metadata(path).map(|metadata| metadata.is_file()).unwrap_or(false)
+}
@@ -0,0 +1,254 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A backend that never leaves the machine, for testing code built on top of `VcsBackend`.
+//! Only test builds have it.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::fs::File;
+use std::hash::{Hash, Hasher, SipHasher};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use dependency::FetchOptions;
+use progress::{Phase, Progress, ProgressSink};
+use project::ZeusProjectError;
+use vcs::VcsBackend;
+
+// The files a commit holds, by path
+type Files = BTreeMap<String, String>;
+
+// Branches and tags point at commits, moving a branch keeps its old commits around
+#[derive(Debug, Default)]
+struct FakeRepository {
+    branches: BTreeMap<String, String>,
+    tags: BTreeMap<String, String>,
+    commits: BTreeMap<String, Files>
+}
+
+impl FakeRepository {
+    // Branches, tags and commit hashes all resolve to a commit
+    fn commit(&self, revision: &str) -> Option<String> {
+        match self.branches.get(revision).or_else(|| self.tags.get(revision)) {
+            Some(commit) => Some(commit.clone()),
+            None if self.commits.contains_key(revision) => Some(String::from(revision)),
+            None => None
+        }
+    }
+
+    fn add_commit(&mut self, url: &str, files: Files) -> String {
+        let commit = fake_hash(url, &files);
+        self.commits.insert(commit.clone(), files);
+        commit
+    }
+}
+
+// What's checked out where, and what was fetched into it
+#[derive(Debug, Clone)]
+struct FakeCheckout {
+    url: String,
+    commit: String,
+    fetched: Option<String>
+}
+
+/// Repositories are set up in memory and cloning one writes its files out. Every call is
+/// recorded so tests can check what would have gone over the network.
+#[derive(Debug, Default)]
+pub struct FakeVcs {
+    repositories: RefCell<HashMap<String, FakeRepository>>,
+    checkouts: RefCell<HashMap<PathBuf, FakeCheckout>>,
+    calls: RefCell<Vec<String>>
+}
+
+impl FakeVcs {
+    pub fn new() -> FakeVcs {
+        FakeVcs::default()
+    }
+
+    /// Points `branch` at a new commit holding `files`, the commits it pointed at before stay around.
+    pub fn add_branch(&self, url: &str, branch: &str, files: &[(&str, &str)]) -> String {
+        let mut repositories = self.repositories.borrow_mut();
+        let repository = repositories.entry(String::from(url)).or_insert(FakeRepository::default());
+        let commit = repository.add_commit(url, to_files(files));
+        repository.branches.insert(String::from(branch), commit.clone());
+        commit
+    }
+
+    pub fn add_tag(&self, url: &str, tag: &str, files: &[(&str, &str)]) -> String {
+        let mut repositories = self.repositories.borrow_mut();
+        let repository = repositories.entry(String::from(url)).or_insert(FakeRepository::default());
+        let commit = repository.add_commit(url, to_files(files));
+        repository.tags.insert(String::from(tag), commit.clone());
+        commit
+    }
+
+    /// Everything asked of the backend so far, like `clone <url> <reference>`.
+    pub fn calls(&self) -> Vec<String> {
+        self.calls.borrow().clone()
+    }
+
+    fn record(&self, call: String) {
+        self.calls.borrow_mut().push(call);
+    }
+
+    fn commit(&self, url: &str, revision: &str) -> Result<String, ZeusProjectError> {
+        match self.repositories.borrow().get(url).and_then(|repository| repository.commit(revision)) {
+            Some(commit) => Ok(commit),
+            None => Err(failed(format!("clone {} {}", url, revision), "no such repository, branch, tag or commit"))
+        }
+    }
+
+    fn files(&self, url: &str, commit: &str) -> Files {
+        self.repositories.borrow()[url].commits[commit].clone()
+    }
+
+    fn checkout_at(&self, path: &Path) -> Result<FakeCheckout, ZeusProjectError> {
+        match self.checkouts.borrow().get(path) {
+            Some(checkout) => Ok(checkout.clone()),
+            None => Err(failed(format!("open {}", path.display()), "not a checkout"))
+        }
+    }
+}
+
+impl VcsBackend for FakeVcs {
+    fn name(&self) -> &'static str { "fake" }
+
+    fn clone(&self, url: &str, path: &Path, reference: &str, _options: &FetchOptions, progress: &ProgressSink)
+        -> Result<(), ZeusProjectError>
+    {
+        self.record(format!("clone {} {}", url, reference));
+
+        let commit = try!(self.commit(url, reference));
+        let files = self.files(url, &commit);
+        try!(write_files(path, &files));
+        progress.report(&Progress::step(Phase::Fetch, "", "Receiving objects", files.len() as u64, files.len() as u64));
+        self.checkouts.borrow_mut().insert(path.to_path_buf(), FakeCheckout {
+            url: String::from(url),
+            commit: commit,
+            fetched: None
+        });
+
+        Ok(())
+    }
+
+    fn fetch(&self, path: &Path, reference: &str, _progress: &ProgressSink) -> Result<(), ZeusProjectError> {
+        self.record(format!("fetch {} {}", path.display(), reference));
+
+        let checkout = try!(self.checkout_at(path));
+        let commit = try!(self.commit(&checkout.url, reference));
+        self.checkouts.borrow_mut().insert(path.to_path_buf(), FakeCheckout {
+            fetched: Some(commit),
+            ..checkout
+        });
+
+        Ok(())
+    }
+
+    fn checkout(&self, path: &Path, revision: &str, _options: &FetchOptions) -> Result<(), ZeusProjectError> {
+        self.record(format!("checkout {} {}", path.display(), revision));
+
+        let checkout = try!(self.checkout_at(path));
+        let commit = match (revision, &checkout.fetched) {
+            ("FETCH_HEAD", &Some(ref fetched)) => fetched.clone(),
+            _ => try!(self.commit(&checkout.url, revision))
+        };
+
+        try!(write_files(path, &self.files(&checkout.url, &commit)));
+        self.checkouts.borrow_mut().insert(path.to_path_buf(), FakeCheckout {
+            commit: commit,
+            ..checkout
+        });
+
+        Ok(())
+    }
+
+    fn rev_parse(&self, path: &Path, revision: &str) -> Result<String, ZeusProjectError> {
+        let checkout = try!(self.checkout_at(path));
+        if revision == "HEAD" {
+            return Ok(checkout.commit);
+        }
+
+        self.commit(&checkout.url, revision)
+    }
+
+    fn status(&self, path: &Path) -> Result<Vec<String>, ZeusProjectError> {
+        let checkout = try!(self.checkout_at(path));
+        let files = self.files(&checkout.url, &checkout.commit);
+
+        // Only files the fake knows about are compared, new files go unnoticed
+        let mut modified = Vec::new();
+        for (name, contents) in files.iter() {
+            let mut current = String::new();
+            let unchanged = File::open(path.join(name))
+                .and_then(|mut file| file.read_to_string(&mut current))
+                .map(|_| current == *contents)
+                .unwrap_or(false);
+            if !unchanged {
+                modified.push(name.clone());
+            }
+        }
+
+        Ok(modified)
+    }
+
+    fn remote_has_ref(&self, url: &str, reference: &str) -> Result<bool, ZeusProjectError> {
+        self.record(format!("ls-remote {} {}", url, reference));
+        Ok(self.repositories.borrow().get(url).map_or(false, |repository| {
+            repository.branches.contains_key(reference) || repository.tags.contains_key(reference)
+        }))
+    }
+
+    fn remote_tags(&self, url: &str) -> Result<Vec<String>, ZeusProjectError> {
+        self.record(format!("ls-remote --tags {}", url));
+        match self.repositories.borrow().get(url) {
+            Some(repository) => Ok(repository.tags.keys().cloned().collect()),
+            None => Err(failed(format!("ls-remote --tags {}", url), "no such repository"))
+        }
+    }
+}
+
+fn to_files(files: &[(&str, &str)]) -> Files {
+    files.iter().map(|&(name, contents)| (String::from(name), String::from(contents))).collect()
+}
+
+fn write_files(path: &Path, files: &Files) -> Result<(), ZeusProjectError> {
+    for (name, contents) in files.iter() {
+        let file_path = path.join(name);
+        if let Some(parent) = file_path.parent() {
+            try!(fs::create_dir_all(parent).map_err(|e| ZeusProjectError::io(parent, e)));
+        }
+
+        let mut file = try!(File::create(&file_path).map_err(|e| ZeusProjectError::io(&file_path, e)));
+        try!(file.write_all(contents.as_bytes()).map_err(|e| ZeusProjectError::io(&file_path, e)));
+    }
+
+    Ok(())
+}
+
+// Stable for the same repository and files, which is all a commit hash needs to be here
+fn fake_hash(url: &str, files: &Files) -> String {
+    let mut hasher = SipHasher::new();
+    url.hash(&mut hasher);
+    files.hash(&mut hasher);
+    format!("{:040x}", hasher.finish())
+}
+
+fn failed(command: String, output: &str) -> ZeusProjectError {
+    ZeusProjectError::CommandFailed {
+        command: format!("fake {}", command),
+        output: String::from(output)
+    }
+}
@@ -0,0 +1,83 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The `cli` backend, looking for git only once something needs it. Building, configuring and
+//! vendored projects never touch git, they work without it installed.
+
+use std::cell::RefCell;
+use std::path::Path;
+use std::rc::Rc;
+
+use config::Config;
+use dependency::FetchOptions;
+use progress::ProgressSink;
+use project::ZeusProjectError;
+use vcs::{CliGit, VcsBackend};
+
+#[derive(Debug)]
+pub struct LazyGit {
+    config: Config,
+    git: RefCell<Option<Rc<CliGit>>>
+}
+
+impl LazyGit {
+    /// Git is discovered the way `CliGit::discover` does it, the first time it's asked for.
+    pub fn new(config: Config) -> LazyGit {
+        LazyGit { config: config, git: RefCell::new(None) }
+    }
+
+    fn git(&self) -> Result<Rc<CliGit>, ZeusProjectError> {
+        if let Some(ref git) = *self.git.borrow() {
+            return Ok(git.clone());
+        }
+
+        let git = Rc::new(try!(CliGit::discover(&self.config)));
+        *self.git.borrow_mut() = Some(git.clone());
+        Ok(git)
+    }
+}
+
+impl VcsBackend for LazyGit {
+    fn name(&self) -> &'static str { "cli" }
+
+    fn clone(&self, url: &str, path: &Path, reference: &str, options: &FetchOptions, progress: &ProgressSink)
+        -> Result<(), ZeusProjectError>
+    {
+        VcsBackend::clone(&*try!(self.git()), url, path, reference, options, progress)
+    }
+
+    fn fetch(&self, path: &Path, reference: &str, progress: &ProgressSink) -> Result<(), ZeusProjectError> {
+        try!(self.git()).fetch(path, reference, progress)
+    }
+
+    fn checkout(&self, path: &Path, revision: &str, options: &FetchOptions) -> Result<(), ZeusProjectError> {
+        try!(self.git()).checkout(path, revision, options)
+    }
+
+    fn rev_parse(&self, path: &Path, revision: &str) -> Result<String, ZeusProjectError> {
+        try!(self.git()).rev_parse(path, revision)
+    }
+
+    fn status(&self, path: &Path) -> Result<Vec<String>, ZeusProjectError> {
+        try!(self.git()).status(path)
+    }
+
+    fn remote_has_ref(&self, url: &str, reference: &str) -> Result<bool, ZeusProjectError> {
+        try!(self.git()).remote_has_ref(url, reference)
+    }
+
+    fn remote_tags(&self, url: &str) -> Result<Vec<String>, ZeusProjectError> {
+        try!(self.git()).remote_tags(url)
+    }
+}
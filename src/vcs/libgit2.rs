@@ -0,0 +1,265 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An in-process backend on top of libgit2, so zeus works without git installed.
+
+use std::cell::Cell;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use git2;
+use git2::{Config, Cred, Direction, ObjectType, RemoteCallbacks, Repository, STATUS_CURRENT, STATUS_IGNORED};
+use git2::build::{CheckoutBuilder, RepoBuilder};
+
+use auth;
+use auth::HostAuth;
+use dependency::FetchOptions;
+use progress::{Phase, Progress, ProgressSink, Unit};
+use project::ZeusProjectError;
+use vcs::VcsBackend;
+
+#[derive(Debug)]
+pub struct LibGit2;
+
+impl LibGit2 {
+    pub fn new() -> LibGit2 {
+        LibGit2
+    }
+
+    // Listing a remote needs a repository to hang the remote off, any empty one will do
+    fn remote_refs(&self, url: &str) -> Result<Vec<String>, ZeusProjectError> {
+        let scratch = try!(scratch_dir());
+        let names = list_remote(&scratch, url);
+
+        let _ = fs::remove_dir_all(&scratch);
+        names
+    }
+}
+
+impl VcsBackend for LibGit2 {
+    fn name(&self) -> &'static str { "libgit2" }
+
+    fn clone(&self, url: &str, path: &Path, reference: &str, options: &FetchOptions, progress: &ProgressSink)
+        -> Result<(), ZeusProjectError>
+    {
+        // libgit2 has no idea what LFS is
+        if options.lfs {
+            return Err(ZeusProjectError::ToolNotFound {
+                tool: String::from("Git LFS support in the libgit2 backend"),
+                hint: String::from("Use the `cli` backend with git-lfs installed for dependencies with `lfs = true`.")
+            });
+        }
+
+        let mut fetch_options = git2::FetchOptions::new();
+        fetch_options.remote_callbacks(try!(callbacks(url, progress)));
+
+        // Asking the builder for a branch only ever finds branches, tags are resolved below
+        let repository = try!(RepoBuilder::new()
+            .fetch_options(fetch_options)
+            .clone(url, path)
+            .map_err(|e| failed("clone", url, e)));
+        try!(check_out_reference(&repository, url, reference, progress));
+
+        if options.submodules {
+            for mut submodule in try!(repository.submodules().map_err(|e| failed("submodule update", url, e))) {
+                try!(submodule.update(true, None).map_err(|e| failed("submodule update", url, e)));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn fetch(&self, path: &Path, reference: &str, progress: &ProgressSink) -> Result<(), ZeusProjectError> {
+        let repository = try!(open(path));
+        let mut remote = try!(repository.find_remote("origin").map_err(|e| failed("fetch", reference, e)));
+        let url = String::from(remote.url().unwrap_or(""));
+
+        let mut fetch_options = git2::FetchOptions::new();
+        fetch_options.remote_callbacks(try!(callbacks(&url, progress)));
+        remote.fetch(&[reference], Some(&mut fetch_options), None).map_err(|e| failed("fetch", &url, e))
+    }
+
+    // Cloning already refused LFS, submodules are left where they are
+    fn checkout(&self, path: &Path, revision: &str, _options: &FetchOptions) -> Result<(), ZeusProjectError> {
+        let repository = try!(open(path));
+        let object = try!(repository.revparse_single(revision).map_err(|e| failed("checkout", revision, e)));
+
+        try!(repository.checkout_tree(&object, Some(CheckoutBuilder::new().force()))
+            .map_err(|e| failed("checkout", revision, e)));
+        repository.set_head_detached(object.id()).map_err(|e| failed("checkout", revision, e))
+    }
+
+    fn rev_parse(&self, path: &Path, revision: &str) -> Result<String, ZeusProjectError> {
+        let repository = try!(open(path));
+        let object = try!(repository.revparse_single(revision).map_err(|e| failed("rev-parse", revision, e)));
+        Ok(object.id().to_string())
+    }
+
+    fn status(&self, path: &Path) -> Result<Vec<String>, ZeusProjectError> {
+        let repository = try!(open(path));
+        let statuses = try!(repository.statuses(None).map_err(|e| failed("status", &path.display().to_string(), e)));
+
+        Ok(statuses.iter()
+            .filter(|entry| entry.status() != STATUS_CURRENT && !entry.status().contains(STATUS_IGNORED))
+            .filter_map(|entry| entry.path().map(String::from))
+            .collect())
+    }
+
+    fn remote_has_ref(&self, url: &str, reference: &str) -> Result<bool, ZeusProjectError> {
+        let candidates = [format!("refs/heads/{}", reference), format!("refs/tags/{}", reference)];
+        Ok(try!(self.remote_refs(url)).iter().any(|name| candidates.contains(name)))
+    }
+
+    fn remote_tags(&self, url: &str) -> Result<Vec<String>, ZeusProjectError> {
+        Ok(try!(self.remote_refs(url)).into_iter()
+            .filter(|name| name.starts_with("refs/tags/") && !name.ends_with("^{}"))
+            .map(|name| String::from(name.trim_left_matches("refs/tags/")))
+            .collect())
+    }
+}
+
+// Answers libgit2's requests for credentials with the ones configured for the url's host,
+// libgit2 never prompts so there's nothing to turn off for CI. Transfers are reported as progress.
+fn callbacks<'a>(url: &str, progress: &'a ProgressSink) -> Result<RemoteCallbacks<'a>, ZeusProjectError> {
+    let host_auth = try!(auth::host_auth(url)).unwrap_or(HostAuth::default());
+    let config = try!(Config::open_default().map_err(|e| failed("config", url, e)));
+
+    // libgit2 keeps asking as long as it gets answers, a second request means the first was wrong
+    let attempts = Cell::new(0);
+
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(move |url, username, allowed| {
+        attempts.set(attempts.get() + 1);
+        if attempts.get() > 2 {
+            return Err(git2::Error::from_str("the configured credentials were rejected"));
+        }
+
+        let username = host_auth.username.as_ref().map(|u| &u[..]).or(username).unwrap_or("git");
+        if allowed.contains(git2::SSH_KEY) {
+            match host_auth.ssh_key {
+                Some(ref key) => Cred::ssh_key(username, None, key, None),
+                None => Cred::ssh_key_from_agent(username)
+            }
+        } else if allowed.contains(git2::USER_PASS_PLAINTEXT) {
+            match host_auth.token() {
+                Some(token) => Cred::userpass_plaintext(username, &token),
+                None => Cred::credential_helper(&config, url, Some(username))
+            }
+        } else {
+            Cred::default()
+        }
+    });
+
+    callbacks.transfer_progress(move |stats| {
+        let (detail, current) = if stats.received_objects() < stats.total_objects() {
+            ("Receiving objects", stats.received_objects())
+        } else {
+            ("Resolving deltas", stats.indexed_deltas())
+        };
+        let total = if current == stats.received_objects() { stats.total_objects() } else { stats.total_deltas() };
+
+        progress.report(&Progress {
+            current: current as u64,
+            total: Some(total as u64),
+            unit: Unit::Objects,
+            ..Progress::new(Phase::Fetch, "", detail)
+        });
+        true
+    });
+
+    Ok(callbacks)
+}
+
+// Checks out the branch or tag a clone was asked for, branches get a local branch like `git clone --branch` makes
+fn check_out_reference(repository: &Repository, url: &str, reference: &str, progress: &ProgressSink)
+    -> Result<(), ZeusProjectError>
+{
+    let branch = format!("refs/remotes/origin/{}", reference);
+    let tag = format!("refs/tags/{}", reference);
+
+    let target = if repository.refname_to_id(&branch).is_ok() {
+        &branch
+    } else {
+        // Tags the default branch doesn't lead to weren't fetched with it
+        let mut fetch_options = git2::FetchOptions::new();
+        fetch_options.remote_callbacks(try!(callbacks(url, progress)));
+        let mut remote = try!(repository.find_remote("origin").map_err(|e| failed("fetch", url, e)));
+        try!(remote.fetch(&[&format!("+{0}:{0}", tag)[..]], Some(&mut fetch_options), None)
+            .map_err(|e| failed("fetch", url, e)));
+        &tag
+    };
+
+    let object = try!(repository.revparse_single(target).and_then(|object| object.peel(ObjectType::Commit))
+        .map_err(|e| failed("checkout", reference, e)));
+    try!(repository.checkout_tree(&object, Some(CheckoutBuilder::new().force()))
+        .map_err(|e| failed("checkout", reference, e)));
+
+    if target == &branch {
+        // The clone already made a local branch for the default one
+        let local = format!("refs/heads/{}", reference);
+        if repository.refname_to_id(&local).is_err() {
+            let commit = try!(repository.find_commit(object.id()).map_err(|e| failed("checkout", reference, e)));
+            try!(repository.branch(reference, &commit, false).map_err(|e| failed("checkout", reference, e)));
+        }
+        repository.set_head(&local).map_err(|e| failed("checkout", reference, e))
+    } else {
+        repository.set_head_detached(object.id()).map_err(|e| failed("checkout", reference, e))
+    }
+}
+
+// Every listing gets its own, more than one zeus may be listing remotes at once
+fn scratch_dir() -> Result<PathBuf, ZeusProjectError> {
+    let temp_dir = env::temp_dir();
+
+    let mut attempt = 0u32;
+    loop {
+        let scratch = temp_dir.join(format!("zeus-ls-remote-{}", attempt));
+        match fs::create_dir(&scratch) {
+            Ok(_) => return Ok(scratch),
+            Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => attempt += 1,
+            Err(e) => return Err(ZeusProjectError::io(&scratch, e))
+        }
+    }
+}
+
+fn list_remote(scratch: &Path, url: &str) -> Result<Vec<String>, ZeusProjectError> {
+    let repository = try!(Repository::init_bare(scratch).map_err(|e| failed("ls-remote", url, e)));
+    let mut remote = try!(repository.remote_anonymous(url).map_err(|e| failed("ls-remote", url, e)));
+    try!(remote.connect(Direction::Fetch).map_err(|e| failed("ls-remote", url, e)));
+
+    let names = try!(remote.list().map_err(|e| failed("ls-remote", url, e)))
+        .iter()
+        .map(|head| String::from(head.name()))
+        .collect();
+    Ok(names)
+}
+
+fn open(path: &Path) -> Result<Repository, ZeusProjectError> {
+    Repository::open(path).map_err(|e| failed("open", &path.display().to_string(), e))
+}
+
+fn failed(operation: &str, target: &str, error: git2::Error) -> ZeusProjectError {
+    if error.code() == git2::ErrorCode::Auth || auth::is_auth_failure(error.message()) {
+        if let Some(host) = auth::host_of(target) {
+            let hint = auth::auth_hint(&host);
+            return ZeusProjectError::AuthenticationFailed { host: host, hint: hint };
+        }
+    }
+
+    ZeusProjectError::CommandFailed {
+        command: auth::redact(&format!("libgit2 {} {}", operation, target), &[]),
+        output: auth::redact(error.message(), &[])
+    }
+}
@@ -0,0 +1,82 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Sends repositories to the mirrors configured under `[mirrors]` instead, for networks that
+//! can't reach the originals or that are faster to a local copy.
+
+use std::path::Path;
+use std::rc::Rc;
+
+use dependency::FetchOptions;
+use progress::ProgressSink;
+use project::ZeusProjectError;
+use vcs::VcsBackend;
+
+#[derive(Debug)]
+pub struct Mirrored {
+    inner: Rc<VcsBackend>,
+    /// Original url and the url to use instead.
+    mirrors: Vec<(String, String)>
+}
+
+impl Mirrored {
+    pub fn new(inner: Rc<VcsBackend>, mirrors: Vec<(String, String)>) -> Mirrored {
+        Mirrored { inner: inner, mirrors: mirrors }
+    }
+
+    /// The url to go to for a repository, itself when it isn't mirrored.
+    pub fn url<'a>(&'a self, url: &'a str) -> &'a str {
+        let trimmed = url.trim_right_matches('/');
+        self.mirrors.iter()
+            .find(|&&(ref original, _)| original.trim_right_matches('/') == trimmed)
+            .map(|&(_, ref mirror)| &mirror[..])
+            .unwrap_or(url)
+    }
+}
+
+impl VcsBackend for Mirrored {
+    fn name(&self) -> &'static str { self.inner.name() }
+
+    fn clone(&self, url: &str, path: &Path, reference: &str, options: &FetchOptions, progress: &ProgressSink)
+        -> Result<(), ZeusProjectError>
+    {
+        // `clone` on its own would be the Rc's
+        VcsBackend::clone(&*self.inner, self.url(url), path, reference, options, progress)
+    }
+
+    // Checkouts already point at their mirror
+    fn fetch(&self, path: &Path, reference: &str, progress: &ProgressSink) -> Result<(), ZeusProjectError> {
+        self.inner.fetch(path, reference, progress)
+    }
+
+    fn checkout(&self, path: &Path, revision: &str, options: &FetchOptions) -> Result<(), ZeusProjectError> {
+        self.inner.checkout(path, revision, options)
+    }
+
+    fn rev_parse(&self, path: &Path, revision: &str) -> Result<String, ZeusProjectError> {
+        self.inner.rev_parse(path, revision)
+    }
+
+    fn status(&self, path: &Path) -> Result<Vec<String>, ZeusProjectError> {
+        self.inner.status(path)
+    }
+
+    fn remote_has_ref(&self, url: &str, reference: &str) -> Result<bool, ZeusProjectError> {
+        self.inner.remote_has_ref(self.url(url), reference)
+    }
+
+    fn remote_tags(&self, url: &str) -> Result<Vec<String>, ZeusProjectError> {
+        self.inner.remote_tags(self.url(url))
+    }
+}
@@ -0,0 +1,115 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Version control backends, everything zeus needs from git goes through `VcsBackend`.
+
+use std::fmt::Debug;
+use std::path::Path;
+use std::rc::Rc;
+
+use config::Config;
+use dependency::FetchOptions;
+use progress::ProgressSink;
+use project::ZeusProjectError;
+
+pub use self::cli::{CliGit, GitOrigin, GIT_VAR};
+pub use self::lazy::LazyGit;
+#[cfg(test)]
+pub use self::fake::FakeVcs;
+pub use self::mirrored::Mirrored;
+#[cfg(feature = "libgit2")]
+pub use self::libgit2::LibGit2;
+
+mod cli;
+#[cfg(test)]
+mod fake;
+mod lazy;
+mod mirrored;
+#[cfg(feature = "libgit2")]
+mod libgit2;
+
+/// Picks the backend when set, `cli` or `libgit2`. It's how the `vcs.backend` setting is overridden.
+pub static VCS_VAR: &'static str = "ZEUS_VCS";
+
+/// What zeus does with repositories. Revisions are anything git understands, like a branch,
+/// a tag, a commit hash or `HEAD`.
+pub trait VcsBackend: Debug {
+    /// How the backend is picked through `ZEUS_VCS`.
+    fn name(&self) -> &'static str;
+
+    /// Clones a single branch or tag of a repository, as shallow as the backend can make it.
+    fn clone(&self, url: &str, path: &Path, reference: &str, options: &FetchOptions, progress: &ProgressSink)
+        -> Result<(), ZeusProjectError>;
+
+    /// Fetches a branch or tag into an existing checkout without checking it out.
+    fn fetch(&self, path: &Path, reference: &str, progress: &ProgressSink) -> Result<(), ZeusProjectError>;
+
+    /// Checks out a revision, bringing the submodules and LFS objects the checkout was cloned with
+    /// along to it.
+    fn checkout(&self, path: &Path, revision: &str, options: &FetchOptions) -> Result<(), ZeusProjectError>;
+
+    /// The commit hash a revision points at.
+    fn rev_parse(&self, path: &Path, revision: &str) -> Result<String, ZeusProjectError>;
+
+    /// Paths in the checkout with local changes, empty when it's clean.
+    fn status(&self, path: &Path) -> Result<Vec<String>, ZeusProjectError>;
+
+    /// Checks a remote repository has a branch or tag, without cloning it.
+    fn remote_has_ref(&self, url: &str, reference: &str) -> Result<bool, ZeusProjectError>;
+
+    /// Lists the tags of a remote repository without cloning it.
+    fn remote_tags(&self, url: &str) -> Result<Vec<String>, ZeusProjectError>;
+}
+
+/// The backend the user's config and environment ask for, outside of any project.
+pub fn default_backend() -> Result<Rc<VcsBackend>, ZeusProjectError> {
+    configured_backend(&try!(Config::load(None)))
+}
+
+/// The backend named by the `vcs.backend` setting, going through any configured mirrors. The `cli`
+/// backend only looks for git once something needs it.
+pub fn configured_backend(config: &Config) -> Result<Rc<VcsBackend>, ZeusProjectError> {
+    let backend = try!(backend(config.get_str("vcs.backend").unwrap_or("cli"), config));
+
+    let mirrors = config.mirrors();
+    if mirrors.is_empty() {
+        Ok(backend)
+    } else {
+        Ok(Rc::new(Mirrored::new(backend, mirrors)))
+    }
+}
+
+pub fn backend(name: &str, config: &Config) -> Result<Rc<VcsBackend>, ZeusProjectError> {
+    match name {
+        "cli" => Ok(Rc::new(LazyGit::new(config.clone()))),
+        "libgit2" => libgit2_backend(),
+        _ => Err(ZeusProjectError::ToolNotFound {
+            tool: format!("a version control backend named `{}`", name),
+            hint: format!("Set {} to `cli` or `libgit2`.", VCS_VAR)
+        })
+    }
+}
+
+#[cfg(feature = "libgit2")]
+fn libgit2_backend() -> Result<Rc<VcsBackend>, ZeusProjectError> {
+    Ok(Rc::new(LibGit2::new()))
+}
+
+#[cfg(not(feature = "libgit2"))]
+fn libgit2_backend() -> Result<Rc<VcsBackend>, ZeusProjectError> {
+    Err(ZeusProjectError::ToolNotFound {
+        tool: String::from("the libgit2 backend"),
+        hint: String::from("This zeus was built without the `libgit2` feature, use the `cli` backend instead.")
+    })
+}
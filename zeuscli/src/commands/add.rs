@@ -0,0 +1,94 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::error::Error;
+use docopt::Docopt;
+use toml::{Table, Value};
+
+use zeus::dependency::Dependency;
+use zeus::lockfile;
+
+use commands;
+
+static USAGE: &'static str = "
+Add a dependency to the project's Zeus.toml and update Zeus.lock.
+
+Usage:
+    zeus add <name> [options]
+
+Options:
+    --git <url>         Depend on a git repository
+    --ref <ref>         The branch or tag of the repository to follow
+    --path <path>       Depend on a directory on disk
+    --version <req>     A version requirement, matched against git tags or
+                        looked up in cargo's registry without --git
+    --submodules        Check out the repository's submodules too
+    --lfs               Pull the repository's Git LFS objects, needs git-lfs
+";
+
+#[derive(RustcDecodable, Debug)]
+struct Flags {
+    arg_name: String,
+    flag_git: Option<String>,
+    flag_ref: Option<String>,
+    flag_path: Option<String>,
+    flag_version: Option<String>,
+    flag_submodules: bool,
+    flag_lfs: bool
+}
+
+pub fn execute() -> Result<(), Box<Error>> {
+    // Parse in the command line flags
+    let flags: Flags = Docopt::new(USAGE)
+        .and_then(|d| d.decode())
+        .unwrap_or_else(|e| e.exit());
+
+    // A bare version is written the short way, everything else as an inline table
+    let plain = !flags.flag_submodules && !flags.flag_lfs;
+    let value = match (&flags.flag_git, &flags.flag_ref, &flags.flag_path, &flags.flag_version) {
+        (&None, &None, &None, &Some(ref version)) if plain => Value::String(version.clone()),
+        _ => {
+            let mut table = Table::new();
+            for (key, value) in vec![("git", &flags.flag_git), ("ref", &flags.flag_ref),
+                                     ("path", &flags.flag_path), ("version", &flags.flag_version)] {
+                if let Some(ref value) = *value {
+                    table.insert(String::from(key), Value::String(value.clone()));
+                }
+            }
+            for (key, enabled) in vec![("submodules", flags.flag_submodules), ("lfs", flags.flag_lfs)] {
+                if enabled {
+                    table.insert(String::from(key), Value::Boolean(true));
+                }
+            }
+            Value::Table(table)
+        }
+    };
+
+    // Make sure it's a dependency we can actually use before touching anything
+    let dependency = try!(Dependency::parse(&flags.arg_name, &value));
+    let project = try!(commands::open_project());
+    try!(project.verify_source(&dependency));
+
+    let key = format!("dependencies.{}", flags.arg_name);
+    let project = try!(project.update_manifest(|editor| editor.set(&key, value)));
+
+    // Updating the manifest locked it already, what was picked is in Zeus.lock
+    let locked = try!(lockfile::load(&project.get_file_path(lockfile::LOCKFILE)));
+    match locked.as_ref().and_then(|locked| locked.package(&dependency.name)).and_then(|p| p.version.as_ref()) {
+        Some(version) => println!("      Added {} {} ({})", dependency.name, version, dependency.source),
+        None => println!("      Added {} ({})", dependency.name, dependency.source)
+    }
+
+    return Ok(());
+}
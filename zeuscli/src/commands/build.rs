@@ -0,0 +1,75 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::error::Error;
+use docopt::Docopt;
+
+use zeus::build::BuildOptions;
+use zeus::profile;
+
+use commands;
+
+static USAGE: &'static str = "
+Compile this project.
+
+Usage:
+    zeus build [options]
+
+Options:
+    --profile <name>            The profile from Zeus.toml to build with, `dev`,
+                                `release` or one of the project's own [default: dev]
+    --features <features>       Features from Zeus.toml's [features] to turn on,
+                                separated by commas, like `editor,athena/telemetry`
+    --no-default-features       Leave the features `default` lists off
+    --target <triple>           Cross-compile for a platform, like
+                                `arm-linux-androideabi`, see `zeus targets`
+";
+
+#[derive(RustcDecodable, Debug)]
+struct Flags {
+    flag_profile: String,
+    flag_features: Option<String>,
+    flag_no_default_features: bool,
+    flag_target: Option<String>
+}
+
+pub fn execute() -> Result<(), Box<Error>> {
+    // Parse in the command line flags
+    let flags: Flags = Docopt::new(USAGE)
+        .and_then(|d| d.decode())
+        .unwrap_or_else(|e| e.exit());
+
+    // Open up the current project
+    let project = try!(commands::open_project());
+
+    // A typo in the profile is easier to fix knowing what there is
+    if let Err(e) = project.profile(&flags.flag_profile) {
+        println!("Profiles in this project: {}", profile::names(project.manifest()).join(", "));
+        return Err(Box::new(e));
+    }
+
+    let options = BuildOptions {
+        profile: flags.flag_profile,
+        features: flags.flag_features.map(|features| {
+            features.split(|c: char| c == ',' || c.is_whitespace()).filter(|f| !f.is_empty()).map(String::from).collect()
+        }).unwrap_or(Vec::new()),
+        default_features: !flags.flag_no_default_features,
+        target: flags.flag_target
+    };
+
+    // Compile the game through the generated cargo project
+    try!(project.build_game(&options));
+
+    return Ok(());
+}
@@ -0,0 +1,66 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use docopt::Docopt;
+
+use zeus::manifest;
+use zeus::project::ZeusProjectError;
+
+static USAGE: &'static str = "
+Validate a Zeus.toml and report every problem found in it.
+
+Usage:
+    zeus check-manifest [<path>]
+
+The path may point at a Zeus.toml or at a project directory, it defaults to
+the current directory. Exits with a non-zero code if any errors were found.
+";
+
+#[derive(RustcDecodable, Debug)]
+struct Flags {
+    arg_path: Option<String>
+}
+
+pub fn execute() -> Result<(), Box<Error>> {
+    // Parse in the command line flags
+    let flags: Flags = Docopt::new(USAGE)
+        .and_then(|d| d.decode())
+        .unwrap_or_else(|e| e.exit());
+
+    // Find the manifest to check
+    let mut path = match flags.arg_path {
+        Some(path) => PathBuf::from(path),
+        None => try!(env::current_dir())
+    };
+    if fs::metadata(&path).map(|m| m.is_dir()).unwrap_or(false) {
+        path.push("Zeus.toml");
+    }
+
+    // Report everything we found
+    let diagnostics = try!(manifest::check_file(&path));
+    for diagnostic in diagnostics.iter() {
+        println!("{}:{}", path.display(), diagnostic);
+    }
+
+    let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+    if errors != 0 {
+        return Err(Box::new(ZeusProjectError::InvalidManifest { path: path, errors: errors }));
+    }
+
+    return Ok(());
+}
@@ -0,0 +1,129 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::env;
+use std::error::Error;
+use std::fs;
+use docopt::Docopt;
+use toml::{Parser, Value};
+
+use zeus::manifest::ManifestEditor;
+use zeus::manifest::editor::format_value;
+use zeus::project::ZeusProjectError;
+
+use commands;
+
+static USAGE: &'static str = "
+Get or set values in the project's Zeus.toml, keeping its formatting intact,
+or list zeus's own settings.
+
+Usage:
+    zeus config get <key>
+    zeus config set <key> <value>
+    zeus config --list [--show-origin]
+
+Options:
+    --list          List the settings that apply here, after layering the
+                    defaults, ~/.zeus/config.toml, the project's
+                    .zeus/config.toml and ZEUS_* environment variables
+    --show-origin   Show where each setting comes from
+
+Keys are dotted paths such as `athena.version`. Values are read as TOML
+when possible (`[\"Jane Doe\"]`, `true`, `3`) and as plain strings otherwise,
+keys that already hold a string and versions like `athena.version` always
+stay strings.
+";
+
+// Keys holding versions or version requirements, which are always strings even when new
+static VERSION_KEYS: &'static [&'static str] = &["version", "zeus_version"];
+
+#[derive(RustcDecodable, Debug)]
+struct Flags {
+    cmd_get: bool,
+    cmd_set: bool,
+    arg_key: String,
+    arg_value: String,
+    flag_list: bool,
+    flag_show_origin: bool
+}
+
+pub fn execute() -> Result<(), Box<Error>> {
+    // Parse in the command line flags
+    let flags: Flags = Docopt::new(USAGE)
+        .and_then(|d| d.decode())
+        .unwrap_or_else(|e| e.exit());
+
+    if flags.flag_list {
+        return list(flags.flag_show_origin);
+    }
+
+    // Going straight to the file keeps zeus usable for fixing a manifest it won't open,
+    // like one whose `zeus_version` this zeus doesn't match
+    let mut manifest_path = try!(env::current_dir());
+    manifest_path.push("Zeus.toml");
+    if fs::metadata(&manifest_path).is_err() {
+        return Err(Box::new(ZeusProjectError::NotAZeusProject));
+    }
+    let mut editor = try!(ManifestEditor::open(&manifest_path));
+
+    let current = editor.get(&flags.arg_key);
+
+    if flags.cmd_get {
+        match current {
+            Some(Value::String(value)) => println!("{}", value),
+            Some(value) => println!("{}", format_value(&value)),
+            None => return Err(Box::new(ZeusProjectError::MissingKey(flags.arg_key)))
+        }
+    } else if flags.cmd_set {
+        let value = parse_value(&flags.arg_key, &flags.arg_value, current.as_ref());
+        try!(editor.set(&flags.arg_key, value));
+        try!(editor.save(&manifest_path));
+    }
+
+    return Ok(());
+}
+
+fn list(show_origin: bool) -> Result<(), Box<Error>> {
+    let config = try!(commands::load_config());
+
+    for setting in config.settings() {
+        let value = match setting.value {
+            Value::String(ref value) => value.clone(),
+            ref value => format_value(value)
+        };
+
+        if show_origin {
+            println!("{}\t{}={}", setting.origin, setting.key, value);
+        } else {
+            println!("{}={}", setting.key, value);
+        }
+    }
+
+    return Ok(());
+}
+
+fn parse_value(key: &str, raw: &str, current: Option<&Value>) -> Value {
+    // A version like `0.2` should not silently turn into a float
+    if let Some(&Value::String(_)) = current {
+        return Value::String(String::from(raw));
+    }
+    if VERSION_KEYS.contains(&key.rsplit('.').next().unwrap_or(key)) {
+        return Value::String(String::from(raw));
+    }
+
+    match Parser::new(&format!("value = {}", raw)).parse() {
+        Some(mut table) => table.remove("value").unwrap_or(Value::String(String::from(raw))),
+        None => Value::String(String::from(raw))
+    }
+}
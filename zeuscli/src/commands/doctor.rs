@@ -0,0 +1,370 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use zeus::compat;
+use zeus::config::Config;
+use zeus::lockfile;
+use zeus::manifest;
+use zeus::project::{ZeusProject, ZeusProjectError};
+use zeus::toolchain;
+use zeus::toolchain::Toolchain;
+use zeus::vcs;
+use zeus::vcs::CliGit;
+
+use commands;
+
+// Older gits can't shallow clone a tag or pick an ssh key through GIT_SSH_COMMAND
+static GIT_REQUIREMENT: &'static str = ">= 2.3.0";
+
+// What fetching Athena and building it takes, roughly
+static DISK_WARN: u64 = 2 * 1024 * 1024 * 1024;
+static DISK_FAIL: u64 = 500 * 1024 * 1024;
+
+// ### Report ###
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Status {
+    Pass,
+    Warn,
+    Fail,
+    Skip
+}
+
+struct Report {
+    warnings: usize,
+    failures: usize
+}
+
+impl Report {
+    fn pass(&mut self, check: &str, detail: &str) {
+        self.line(Status::Pass, check, detail, None);
+    }
+
+    fn warn(&mut self, check: &str, detail: &str, hint: &str) {
+        self.line(Status::Warn, check, detail, Some(hint));
+    }
+
+    fn fail(&mut self, check: &str, detail: &str, hint: &str) {
+        self.line(Status::Fail, check, detail, Some(hint));
+    }
+
+    fn skip(&mut self, check: &str, detail: &str) {
+        self.line(Status::Skip, check, detail, None);
+    }
+
+    fn line(&mut self, status: Status, check: &str, detail: &str, hint: Option<&str>) {
+        let label = match status {
+            Status::Pass => "pass",
+            Status::Warn => "warn",
+            Status::Fail => "fail",
+            Status::Skip => "skip"
+        };
+        match status {
+            Status::Warn => self.warnings += 1,
+            Status::Fail => self.failures += 1,
+            _ => {}
+        }
+
+        println!("[{}] {}: {}", label, check, detail);
+        if let Some(hint) = hint {
+            println!("       fix: {}", hint);
+        }
+    }
+}
+
+
+// ### Command ###
+
+pub fn execute() -> Result<(), Box<Error>> {
+    let mut report = Report { warnings: 0, failures: 0 };
+
+    report.pass("zeus", &format!("{} ({})", compat::ZEUS_VERSION, compat::ZEUS_CHANNEL));
+
+    let config = try!(commands::load_config());
+    check_tools(&mut report, &config);
+    check_cache(&mut report, &config);
+
+    let directory = try!(env::current_dir());
+    if directory.join("Zeus.toml").exists() {
+        check_project(&mut report, &directory, config);
+    } else {
+        report.skip("project", "not in a zeus project, run zeus doctor in one to check it too");
+    }
+
+    // Failures fail the command, so doctor can gate CI and scripts
+    if report.failures != 0 {
+        println!("");
+        return Err(From::from(format!("{} check(s) failed, {} warning(s).", report.failures, report.warnings)));
+    } else if report.warnings != 0 {
+        println!("\nNothing failed, but there are {} warning(s).", report.warnings);
+    } else {
+        println!("\nEverything looks good.");
+    }
+
+    return Ok(());
+}
+
+fn check_tools(report: &mut Report, config: &Config) {
+    // The backend everything goes through, a broken one stops setup cold
+    match vcs::configured_backend(config) {
+        Ok(backend) => {
+            let origin = config.get("vcs.backend").map(|setting| setting.origin.to_string()).unwrap_or(String::from("default"));
+            report.pass("version control", &format!("{} ({})", backend.name(), origin));
+        },
+        Err(e) => report.fail("version control", &e.to_string(), "set `vcs.backend` to `cli` or `libgit2`")
+    }
+
+    // Even the libgit2 backend needs git around for LFS
+    match CliGit::discover(config) {
+        Ok(git) => match git.version() {
+            Ok(version) => {
+                let detail = format!("{} ({}), {}", git.path().display(), git.origin(), version);
+                check_version(report, "git", &detail, &version, GIT_REQUIREMENT, "install a newer git from https://git-scm.com");
+            },
+            Err(e) => report.fail("git", &format!("{} ({}) doesn't run: {}", git.path().display(), git.origin(), e),
+                "reinstall git, or point `git.path` at a working one")
+        },
+        Err(e) => report.fail("git", &e.to_string(), "install git from https://git-scm.com, or set `git.path`")
+    }
+
+    let rust_hint = format!("install a {} rust matching {} from https://www.rust-lang.org",
+        compat::RUST_CHANNEL, compat::RUSTC_REQUIREMENT);
+    match tool_version("rustc") {
+        Ok(version) => {
+            if check_version(report, "rustc", &version, &version, compat::RUSTC_REQUIREMENT, &rust_hint)
+                && !version.contains(&format!("-{}", compat::RUST_CHANNEL)) {
+                report.warn("rust channel", &format!("{} isn't a {} compiler, Athena uses unstable features", version,
+                    compat::RUST_CHANNEL), &rust_hint);
+            }
+        },
+        Err(e) => report.fail("rustc", &format!("can't run rustc: {}", e), &rust_hint)
+    }
+
+    let cargo_hint = "cargo comes with rust, reinstall rust from https://www.rust-lang.org";
+    match tool_version("cargo") {
+        Ok(version) => { check_version(report, "cargo", &version, &version, compat::CARGO_REQUIREMENT, cargo_hint); },
+        Err(e) => report.fail("cargo", &format!("can't run cargo: {}", e), cargo_hint)
+    }
+}
+
+fn check_cache(report: &mut Report, config: &Config) {
+    let cache_dir = match config.cache_dir() {
+        Some(cache_dir) => cache_dir,
+        None => return report.warn("cache", "there's no cache directory, registry downloads won't be cached",
+            "set `cache.dir` in ~/.zeus/config.toml")
+    };
+
+    match check_writable(&cache_dir) {
+        Ok(_) if cache_dir.exists() => report.pass("cache", &format!("{} is writable", cache_dir.display())),
+        Ok(_) => report.pass("cache", &format!("{} doesn't exist yet but can be created", cache_dir.display())),
+        Err(e) => report.fail("cache", &format!("can't write to {}: {}", cache_dir.display(), e),
+            "fix the directory's permissions or point `cache.dir` somewhere else")
+    }
+}
+
+fn check_project(report: &mut Report, directory: &Path, config: Config) {
+    match check_writable(directory) {
+        Ok(_) => report.pass("project directory", &format!("{} is writable", directory.display())),
+        Err(e) => report.fail("project directory", &format!("can't write to {}: {}", directory.display(), e),
+            "fix the project directory's permissions")
+    }
+    check_disk_space(report, directory);
+
+    let manifest_path = directory.join("Zeus.toml");
+    match manifest::check_file(&manifest_path) {
+        Ok(diagnostics) => {
+            let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+            let warnings = diagnostics.len() - errors;
+            let hint = "run `zeus check-manifest` to see every problem";
+            if errors != 0 {
+                report.fail("Zeus.toml", &format!("{} error(s), {} warning(s)", errors, warnings), hint);
+            } else if warnings != 0 {
+                report.warn("Zeus.toml", &format!("{} warning(s)", warnings), hint);
+            } else {
+                report.pass("Zeus.toml", "valid");
+            }
+        },
+        Err(e) => report.fail("Zeus.toml", &e.to_string(), "make sure Zeus.toml is readable")
+    }
+
+    let project = match ZeusProject::open_with_config(directory.to_path_buf(), config) {
+        Ok(project) => project,
+        Err(e) => return report.fail("project", &e.to_string(), "fix the problem above, then run zeus doctor again")
+    };
+    for warning in project.warnings() {
+        report.warn("project", warning, "see `zeus check-manifest` and `zeus migrate`");
+    }
+
+    check_toolchain(report, &project);
+    check_athena(report, &project);
+}
+
+fn check_toolchain(report: &mut Report, project: &ZeusProject) {
+    let toolchain = match Toolchain::from_manifest(project.manifest()) {
+        Ok(Some(toolchain)) => toolchain,
+        Ok(None) => return report.skip("toolchain", "the project doesn't pin a rust toolchain"),
+        Err(reason) => return report.fail("toolchain", &format!("[toolchain] is invalid: {}", reason),
+            "run `zeus check-manifest` and fix the [toolchain] table")
+    };
+
+    match toolchain::installed_version(&toolchain) {
+        Ok(Some(version)) => report.pass("toolchain", &format!("{} is installed, {}", toolchain.name(), version)),
+        Ok(None) if toolchain::has_rustup() => report.fail("toolchain", &format!("{} isn't installed", toolchain.name()),
+            &format!("run `zeus setup` or `rustup toolchain install {}`", toolchain.name())),
+        Ok(None) => report.fail("toolchain", &format!("rustc isn't from {}", toolchain.name()),
+            "install rustup from https://www.rust-lang.org so zeus can install the toolchain"),
+        Err(e) => report.fail("toolchain", &e.to_string(), "install rust from https://www.rust-lang.org")
+    }
+}
+
+// Athena has to be what Zeus.lock says, at the commit it names and without local changes
+fn check_athena(report: &mut Report, project: &ZeusProject) {
+    let setup_hint = "run `zeus setup`";
+
+    let locked = match lockfile::load(&project.get_file_path(lockfile::LOCKFILE)) {
+        Ok(Some(resolve)) => resolve,
+        Ok(None) => return report.warn("athena", "the project has no Zeus.lock yet", setup_hint),
+        Err(e) => return report.fail("athena", &e.to_string(), "delete Zeus.lock and run `zeus setup`")
+    };
+    let package = match locked.package("athena") {
+        Some(package) => package,
+        None => return report.fail("athena", "Zeus.lock doesn't lock Athena", setup_hint)
+    };
+
+    let path = project.get_file_path(project.package_path("athena"));
+    if !path.exists() {
+        return report.warn("athena", &format!("{} hasn't been fetched", path.display()), setup_hint);
+    }
+
+    let modified_hint = format!("commit or stash your changes to {}, or delete it and run `zeus setup`", path.display());
+
+    // Vendored copies have no history, their checksum is all there is to go on
+    if path.join(".git").exists() {
+        let vcs = project.vcs();
+        match (vcs.rev_parse(&path, "HEAD"), package.commit.as_ref()) {
+            (Ok(ref head), Some(commit)) if head != commit => {
+                return report.fail("athena", &format!("{} is at {} but Zeus.lock locked {}",
+                    path.display(), short(head), short(commit)), setup_hint);
+            },
+            (Ok(_), None) => report.warn("athena", "Zeus.lock doesn't record which commit Athena was fetched at",
+                "run `zeus setup` to lock it"),
+            (Err(e), _) => {
+                return report.fail("athena", &format!("can't tell which commit {} is at: {}", path.display(), e), setup_hint);
+            },
+            _ => {}
+        }
+
+        match vcs.status(&path) {
+            Ok(ref changes) if !changes.is_empty() => {
+                return report.fail("athena", &format!("{} file(s) in {} have local changes, like {}",
+                    changes.len(), path.display(), changes[0]), &modified_hint);
+            },
+            Err(e) => return report.fail("athena", &format!("can't check {} for local changes: {}", path.display(), e),
+                &modified_hint),
+            _ => {}
+        }
+    }
+
+    let mut athena = locked.clone();
+    athena.packages.retain(|package| package.name == "athena");
+    match project.verify(&athena, false) {
+        Ok(_) => report.pass("athena", &format!("{} matches Zeus.lock", path.display())),
+        Err(ZeusProjectError::ChecksumMismatch { .. }) =>
+            report.fail("athena", &format!("{} doesn't match the checksum in Zeus.lock", path.display()), &modified_hint),
+        Err(e) => report.fail("athena", &e.to_string(), setup_hint)
+    }
+}
+
+fn check_disk_space(report: &mut Report, directory: &Path) {
+    let hint = "free up some space, fetching and building Athena takes about 2 GiB";
+    match free_space(directory) {
+        Some(free) if free < DISK_FAIL => report.fail("disk space", &format!("only {} free", megabytes(free)), hint),
+        Some(free) if free < DISK_WARN => report.warn("disk space", &format!("only {} free", megabytes(free)), hint),
+        Some(free) => report.pass("disk space", &format!("{} free", megabytes(free))),
+        None => report.skip("disk space", "can't tell how much space is free on this platform")
+    }
+}
+
+// Reports whether the version in a tool's output meets the requirement, passing if it does
+fn check_version(report: &mut Report, check: &str, detail: &str, output: &str, requirement: &str, hint: &str) -> bool {
+    match compat::parse_tool_version(output) {
+        Some(ref version) if compat::tool_meets(version, requirement) => {
+            report.pass(check, detail);
+            true
+        },
+        Some(version) => {
+            report.fail(check, &format!("{} is too old, Athena needs {}", version, requirement), hint);
+            false
+        },
+        None => {
+            report.warn(check, &format!("can't tell which version `{}` is", output), hint);
+            false
+        }
+    }
+}
+
+fn tool_version(tool: &str) -> io::Result<String> {
+    let output = try!(Command::new(tool).arg("--version").output());
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// Directories that don't exist yet are fine as long as they can be made, doctor doesn't make them
+fn check_writable(dir: &Path) -> io::Result<()> {
+    let mut existing = dir.to_path_buf();
+    while !existing.exists() {
+        if !existing.pop() {
+            existing = PathBuf::from(".");
+            break;
+        }
+    }
+
+    let probe = existing.join(".zeus-doctor");
+    try!(File::create(&probe));
+    fs::remove_file(&probe)
+}
+
+#[cfg(unix)]
+fn free_space(dir: &Path) -> Option<u64> {
+    // POSIX df prints a header, then `filesystem blocks used available capacity mount` in kilobytes
+    let output = match Command::new("df").arg("-Pk").arg(dir).output() {
+        Ok(ref output) if output.status.success() => String::from_utf8_lossy(&output.stdout).into_owned(),
+        _ => return None
+    };
+
+    output.lines().nth(1)
+        .and_then(|line| line.split_whitespace().nth(3))
+        .and_then(|available| available.parse::<u64>().ok())
+        .map(|kilobytes| kilobytes * 1024)
+}
+
+#[cfg(not(unix))]
+fn free_space(_dir: &Path) -> Option<u64> {
+    None
+}
+
+fn megabytes(bytes: u64) -> String {
+    format!("{} MiB", bytes / (1024 * 1024))
+}
+
+fn short(hash: &str) -> &str {
+    &hash[..hash.len().min(10)]
+}
@@ -0,0 +1,106 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::env;
+use std::error::Error;
+use std::fs;
+use docopt::Docopt;
+
+use zeus::manifest::migrate;
+use zeus::project::ZeusProjectError;
+
+static USAGE: &'static str = "
+Update the project's Zeus.toml to the current manifest format.
+
+Usage:
+    zeus migrate [--dry-run]
+
+Options:
+    --dry-run   Show what would change without touching Zeus.toml
+
+The original manifest is kept next to it as Zeus.toml.v<version>.bak, or
+Zeus.toml.v<version>.<n>.bak when an earlier backup is already there.
+";
+
+#[derive(RustcDecodable, Debug)]
+struct Flags {
+    flag_dry_run: bool
+}
+
+pub fn execute() -> Result<(), Box<Error>> {
+    // Parse in the command line flags
+    let flags: Flags = Docopt::new(USAGE)
+        .and_then(|d| d.decode())
+        .unwrap_or_else(|e| e.exit());
+
+    // Opening the project would complain about the very thing we're fixing, go straight to the file
+    let mut path = try!(env::current_dir());
+    path.push("Zeus.toml");
+    if fs::metadata(&path).is_err() {
+        return Err(Box::new(ZeusProjectError::NotAZeusProject));
+    }
+
+    let outcome = try!(migrate::migrate_file(&path, flags.flag_dry_run));
+    if outcome.is_up_to_date() {
+        println!("Zeus.toml is already at schema version {}.", outcome.to);
+        return Ok(());
+    }
+
+    println!("Migrating Zeus.toml from schema version {} to {}:", outcome.from, outcome.to);
+    for step in outcome.steps.iter() {
+        println!("  - {}", step);
+    }
+    println!("");
+    print_diff(&outcome.original, &outcome.migrated);
+
+    match outcome.backup {
+        Some(ref backup) => println!("\nThe original manifest was saved to {}.", backup.display()),
+        None => println!("\nDry run, nothing was written.")
+    }
+
+    return Ok(());
+}
+
+// A plain line diff, manifests are small enough for the quadratic version
+fn print_diff(original: &str, migrated: &str) {
+    let old: Vec<&str> = original.lines().collect();
+    let new: Vec<&str> = migrated.lines().collect();
+
+    // Longest common subsequence lengths of every pair of suffixes
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                ::std::cmp::max(lengths[i + 1][j], lengths[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            println!("  {}", old[i]);
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lengths[i][j + 1] >= lengths[i + 1][j]) {
+            println!("+ {}", new[j]);
+            j += 1;
+        } else {
+            println!("- {}", old[i]);
+            i += 1;
+        }
+    }
+}
@@ -12,6 +12,54 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::env;
+use std::error::Error;
+use std::rc::Rc;
+
+use zeus::config::Config;
+use zeus::project::ZeusProject;
+
+use progress::ProgressBar;
+
+pub mod add;
+pub mod build;
+pub mod check_manifest;
+pub mod config;
+pub mod doctor;
 pub mod list;
+pub mod migrate;
 pub mod new;
+pub mod package;
+pub mod publish;
+pub mod remove;
+pub mod search;
 pub mod setup;
+pub mod targets;
+pub mod tree;
+pub mod vendor;
+pub mod verify;
+
+/// The settings that apply in the current directory, for commands to layer their flags on.
+pub fn load_config() -> Result<Config, Box<Error>> {
+    let path = try!(env::current_dir());
+    Ok(try!(Config::load(Some(&path))))
+}
+
+/// Opens the project in the current directory, reporting anything worth knowing about it and
+/// showing the progress of whatever it's asked to do.
+pub fn open_project() -> Result<ZeusProject, Box<Error>> {
+    open_project_with(try!(load_config()))
+}
+
+/// Opens the project in the current directory with settings from `load_config` and the command's flags.
+pub fn open_project_with(config: Config) -> Result<ZeusProject, Box<Error>> {
+    let path = try!(env::current_dir());
+    let mut project = try!(ZeusProject::open_with_config(path, config));
+    project.set_progress(Rc::new(ProgressBar::new()));
+
+    for warning in project.warnings() {
+        println!("warning: {}", warning);
+    }
+
+    Ok(project)
+}
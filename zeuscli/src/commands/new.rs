@@ -16,18 +16,23 @@ use std::error::Error;
 use std::path::PathBuf;
 use docopt::Docopt;
 
+use zeus::config::{Config, Origin};
 use zeus::project::ZeusProject;
 
 static USAGE: &'static str = "
 Athena's project build system.
 
 Usage:
-    zeus new <path>
+    zeus new <path> [options]
+
+Options:
+    --author <name>     The author to put in Zeus.toml, defaults to the `user.author` setting
 ";
 
 #[derive(RustcDecodable, Debug)]
 struct Flags {
-    arg_path: String
+    arg_path: String,
+    flag_author: Option<String>
 }
 
 pub fn execute() -> Result<(), Box<Error>> {
@@ -36,9 +41,14 @@ pub fn execute() -> Result<(), Box<Error>> {
         .and_then(|d| d.decode())
         .unwrap_or_else(|e| e.exit());
 
+    let mut config = try!(Config::load(None));
+    if let Some(ref author) = flags.flag_author {
+        try!(config.set("user.author", author, Origin::CommandLine));
+    }
+
     // Create a new project
     let path = PathBuf::from(flags.arg_path);
-    try!(ZeusProject::create(path));
+    try!(ZeusProject::create_with_config(path, config));
 
     return Ok(());
 }
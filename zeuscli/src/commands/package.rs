@@ -0,0 +1,31 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::error::Error;
+
+use zeus::registry;
+
+use commands;
+
+pub fn execute() -> Result<(), Box<Error>> {
+    // Open up the current project
+    let project = try!(commands::open_project());
+
+    // Pack it up without publishing it anywhere
+    let package = try!(registry::package(&project));
+    println!("   Packaged {} {} ({})", package.entry.name, package.entry.version, package.path.display());
+    println!("   Checksum {}", package.entry.checksum);
+
+    return Ok(());
+}
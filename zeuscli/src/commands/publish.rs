@@ -0,0 +1,56 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::error::Error;
+use docopt::Docopt;
+
+use zeus::auth;
+use zeus::registry;
+use zeus::registry::Registry;
+
+use commands;
+
+static USAGE: &'static str = "
+Package the project and publish it to a registry.
+
+Usage:
+    zeus publish --registry <registry>
+
+Options:
+    --registry <registry>   The registry directory to publish to, registries
+                            served over HTTP can only be read from
+";
+
+#[derive(RustcDecodable, Debug)]
+struct Flags {
+    flag_registry: String
+}
+
+pub fn execute() -> Result<(), Box<Error>> {
+    // Parse in the command line flags
+    let flags: Flags = Docopt::new(USAGE)
+        .and_then(|d| d.decode())
+        .unwrap_or_else(|e| e.exit());
+
+    // Open up the current project
+    let project = try!(commands::open_project());
+
+    let registry = try!(Registry::open_from_working_dir(&flags.flag_registry, project.config()));
+    try!(registry.check_publishable());
+    let package = try!(registry::package(&project));
+    try!(registry.publish(&package));
+    println!("  Published {} {} to {}", package.entry.name, package.entry.version, auth::redact(&flags.flag_registry, &[]));
+
+    return Ok(());
+}
@@ -0,0 +1,55 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::error::Error;
+use docopt::Docopt;
+
+use zeus::project::ZeusProjectError;
+
+use commands;
+
+static USAGE: &'static str = "
+Remove a dependency from the project's Zeus.toml and update Zeus.lock.
+
+Usage:
+    zeus remove <name>
+";
+
+#[derive(RustcDecodable, Debug)]
+struct Flags {
+    arg_name: String
+}
+
+pub fn execute() -> Result<(), Box<Error>> {
+    // Parse in the command line flags
+    let flags: Flags = Docopt::new(USAGE)
+        .and_then(|d| d.decode())
+        .unwrap_or_else(|e| e.exit());
+
+    let project = try!(commands::open_project());
+    let key = format!("dependencies.{}", flags.arg_name);
+    let project = try!(project.update_manifest(|editor| {
+        if try!(editor.remove(&key)) {
+            Ok(())
+        } else {
+            Err(ZeusProjectError::MissingKey(key.clone()))
+        }
+    }));
+
+    // The old checkout is of no use anymore
+    try!(project.remove_checkout(&flags.arg_name));
+
+    println!("    Removed {}", flags.arg_name);
+    return Ok(());
+}
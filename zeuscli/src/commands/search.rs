@@ -0,0 +1,109 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use docopt::Docopt;
+use rustc_serialize::json::Json;
+
+use zeus::config::Origin;
+use zeus::project::ZeusProjectError;
+use zeus::registry::{IndexEntry, Registry};
+
+use commands;
+
+static USAGE: &'static str = "
+Search a package registry for Athena plugins and asset packs.
+
+Usage:
+    zeus search <query> [options]
+
+Options:
+    --registry <registry>   The registry to search, a directory or an HTTP url.
+                            Defaults to the `registry.default` setting
+    --limit <n>             Show at most this many packages [default: 10]
+    --json                  Print the results as JSON
+";
+
+#[derive(RustcDecodable, Debug)]
+struct Flags {
+    arg_query: String,
+    flag_registry: Option<String>,
+    flag_limit: usize,
+    flag_json: bool
+}
+
+pub fn execute() -> Result<(), Box<Error>> {
+    // Parse in the command line flags
+    let flags: Flags = Docopt::new(USAGE)
+        .and_then(|d| d.decode())
+        .unwrap_or_else(|e| e.exit());
+
+    let mut config = try!(commands::load_config());
+    if let Some(ref registry) = flags.flag_registry {
+        try!(config.set("registry.default", registry, Origin::CommandLine));
+    }
+
+    let location = match config.get_str("registry.default") {
+        Some(location) => String::from(location),
+        None => {
+            println!("No registry to search, pass --registry or set `registry.default`.");
+            return Err(Box::new(ZeusProjectError::MissingKey(String::from("registry.default"))));
+        }
+    };
+
+    let registry = try!(Registry::open_from_working_dir(&location, &config));
+    let found = try!(registry.search(&flags.arg_query));
+    let shown = &found[..found.len().min(flags.flag_limit)];
+
+    if flags.flag_json {
+        let packages: Vec<Json> = shown.iter().map(to_json).collect();
+        println!("{}", Json::Array(packages).pretty());
+        return Ok(());
+    }
+
+    if found.is_empty() {
+        println!("No packages match `{}`.", flags.arg_query);
+        return Ok(());
+    }
+
+    for entry in shown {
+        let mut line = format!("{} = \"{}\"", entry.name, entry.version);
+        if let Some(ref description) = entry.description {
+            line = format!("{:<32} # {}", line, description);
+        }
+        if !entry.keywords.is_empty() {
+            line = format!("{} [{}]", line, entry.keywords.join(", "));
+        }
+        println!("{}", line);
+    }
+
+    if found.len() > shown.len() {
+        println!("... and {} more, use --limit to see them", found.len() - shown.len());
+    }
+
+    return Ok(());
+}
+
+fn to_json(entry: &IndexEntry) -> Json {
+    let mut object = BTreeMap::new();
+    object.insert(String::from("name"), Json::String(entry.name.clone()));
+    object.insert(String::from("version"), Json::String(entry.version.to_string()));
+    object.insert(String::from("description"), match entry.description {
+        Some(ref description) => Json::String(description.clone()),
+        None => Json::Null
+    });
+    object.insert(String::from("keywords"), Json::Array(entry.keywords.iter().map(|k| Json::String(k.clone())).collect()));
+    Json::Object(object)
+}
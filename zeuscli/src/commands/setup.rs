@@ -13,17 +13,49 @@
 // limitations under the License.
 
 use std::error::Error;
-use std::env;
+use docopt::Docopt;
 
-use zeus::project::ZeusProject;
+use zeus::config::Origin;
+
+use commands;
+
+static USAGE: &'static str = "
+Fetch Athena and the game's dependencies and hook them up to cargo.
+
+Usage:
+    zeus setup [options]
+
+Options:
+    --offline   Only use what's already fetched, Zeus.lock has to match Zeus.toml
+";
+
+#[derive(RustcDecodable, Debug)]
+struct Flags {
+    flag_offline: bool
+}
 
 pub fn execute() -> Result<(), Box<Error>> {
+    // Parse in the command line flags
+    let flags: Flags = Docopt::new(USAGE)
+        .and_then(|d| d.decode())
+        .unwrap_or_else(|e| e.exit());
+
+    let mut config = try!(commands::load_config());
+    if flags.flag_offline {
+        try!(config.set("net.offline", "true", Origin::CommandLine));
+    }
+
     // Open up the current project
-    let path = env::current_dir().unwrap();
-    let project = try!(ZeusProject::open(path));
+    let project = try!(commands::open_project_with(config));
 
-    // Build all the tools, currently only the editor
-    project.build_editor();
+    // Fetch Athena and the game's dependencies and hook them up to cargo
+    let resolve = try!(project.setup());
+    for package in resolve.packages.iter() {
+        match package.version {
+            Some(ref version) => println!("    Locked {} {} ({})", package.name, version, package.source),
+            None => println!("    Locked {} ({})", package.name, package.source)
+        }
+    }
 
     return Ok(());
 }
@@ -0,0 +1,65 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::error::Error;
+
+use zeus::target;
+
+use commands;
+
+pub fn execute() -> Result<(), Box<Error>> {
+    // Open up the current project
+    let project = try!(commands::open_project());
+
+    let targets = target::targets(project.manifest());
+    if targets.is_empty() {
+        println!("No targets are configured, add a [target.<triple>] table to Zeus.toml.");
+        return Ok(());
+    }
+
+    // Ask rustup once for everything the pinned toolchain has
+    let toolchain = try!(project.toolchain());
+    let installed = target::installed_std(toolchain.as_ref());
+    let toolchain_arg = toolchain.as_ref().map(|toolchain| format!(" --toolchain {}", toolchain.name())).unwrap_or(String::new());
+
+    for &(ref triple, ref target) in targets.iter() {
+        // Broken targets are listed too, hiding them would hide the mistake
+        let target = match *target {
+            Ok(ref target) => target,
+            Err(ref reason) => {
+                println!("{} (invalid: {})", triple, reason);
+                continue;
+            }
+        };
+
+        let status = match installed {
+            Some(ref installed) if installed.contains(&target.triple) => String::from("installed"),
+            Some(_) => format!("missing, run `rustup target add {}{}`", target.triple, toolchain_arg),
+            None => String::from("unknown, rustup is not installed")
+        };
+        println!("{} (std {})", target.triple, status);
+
+        if let Some(ref linker) = target.linker {
+            println!("    linker: {}", linker);
+        }
+        if !target.features.is_empty() {
+            println!("    features: {}", target.features.join(", "));
+        }
+        if let Some(ref assets) = target.assets {
+            println!("    assets: {}", assets);
+        }
+    }
+
+    return Ok(());
+}
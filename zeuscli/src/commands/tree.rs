@@ -0,0 +1,247 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use docopt::Docopt;
+
+use zeus::dependency::Source;
+use zeus::project::ZeusProjectError;
+use zeus::resolver::{Resolve, ResolvedPackage};
+
+use commands;
+
+static USAGE: &'static str = "
+Display the project's resolved dependency graph.
+
+Usage:
+    zeus tree [--invert <package>] [--format <format>]
+    zeus tree --duplicates [--format <format>]
+
+Options:
+    --duplicates        Only show sources that are in the graph more than once,
+                        at different versions or references
+    --invert <package>  Show what depends on a package instead of what it depends on
+    --format <format>   Either `text` or `dot` for Graphviz [default: text]
+";
+
+#[derive(RustcDecodable, Debug)]
+struct Flags {
+    flag_duplicates: bool,
+    flag_invert: Option<String>,
+    flag_format: String
+}
+
+pub fn execute() -> Result<(), Box<Error>> {
+    // Parse in the command line flags
+    let flags: Flags = Docopt::new(USAGE)
+        .and_then(|d| d.decode())
+        .unwrap_or_else(|e| e.exit());
+
+    // Resolve the graph, this uses Zeus.lock when it's up to date
+    let project = try!(commands::open_project());
+    let resolve = try!(project.resolve());
+    let graph = Graph::new(project.game_name(), &resolve);
+
+    let start = match flags.flag_invert {
+        Some(ref name) => {
+            if resolve.package(name).is_none() {
+                return Err(Box::new(ZeusProjectError::InvalidDependency {
+                    name: name.clone(),
+                    reason: String::from("it is not part of the resolved dependency graph")
+                }));
+            }
+            name.clone()
+        },
+        None => String::new()
+    };
+    let inverted = flags.flag_invert.is_some();
+    let duplicates = graph.duplicates();
+
+    match &flags.flag_format[..] {
+        "dot" => graph.print_dot(&start, inverted, &duplicates),
+        "text" if flags.flag_duplicates => graph.print_duplicates(&duplicates),
+        "text" => graph.print_tree(&start, inverted),
+        other => return Err(Box::new(ZeusProjectError::InvalidKey(format!("--format {}", other))))
+    }
+
+    return Ok(());
+}
+
+
+// ### Graph ###
+
+// Nodes are package names, the game itself is the empty name
+struct Graph {
+    labels: BTreeMap<String, String>,
+    forward: BTreeMap<String, Vec<String>>,
+    backward: BTreeMap<String, Vec<String>>,
+    packages: Vec<ResolvedPackage>
+}
+
+impl Graph {
+    fn new(game_name: &str, resolve: &Resolve) -> Graph {
+        let mut graph = Graph {
+            labels: BTreeMap::new(),
+            forward: BTreeMap::new(),
+            backward: BTreeMap::new(),
+            packages: resolve.packages.clone()
+        };
+
+        graph.labels.insert(String::new(), String::from(game_name));
+        for root in resolve.roots.iter() {
+            graph.add_edge("", root);
+        }
+
+        for package in resolve.packages.iter() {
+            graph.labels.insert(package.name.clone(), label(package));
+            for dependency in package.dependencies.iter() {
+                graph.add_edge(&package.name, dependency);
+            }
+        }
+
+        graph
+    }
+
+    fn add_edge(&mut self, from: &str, to: &str) {
+        self.forward.entry(String::from(from)).or_insert(Vec::new()).push(String::from(to));
+        self.backward.entry(String::from(to)).or_insert(Vec::new()).push(String::from(from));
+    }
+
+    fn edges(&self, inverted: bool) -> &BTreeMap<String, Vec<String>> {
+        if inverted { &self.backward } else { &self.forward }
+    }
+
+    fn label(&self, node: &str) -> &str {
+        self.labels.get(node).map(|l| &l[..]).unwrap_or(node)
+    }
+
+    /// Groups of packages that come from the same place at different versions or references,
+    /// e.g. the same repository at two tags under different names. Registry packages are known
+    /// by their name, which the graph only has once.
+    fn duplicates(&self) -> Vec<Vec<&ResolvedPackage>> {
+        let mut groups: BTreeMap<String, Vec<&ResolvedPackage>> = BTreeMap::new();
+        for package in self.packages.iter() {
+            let location = match package.source {
+                Source::Git { ref url, .. } => url.trim_right_matches(".git").trim_right_matches('/').to_lowercase(),
+                Source::Path(ref path) => path.display().to_string(),
+                Source::Registry => package.name.clone(),
+                Source::ZeusRegistry(ref registry) => format!("{} {}", registry, package.name)
+            };
+            groups.entry(location).or_insert(Vec::new()).push(package);
+        }
+
+        groups.into_iter()
+            .map(|(_, group)| group)
+            .filter(|group| {
+                let picked: BTreeSet<(Option<String>, Option<String>)> = group.iter()
+                    .map(|package| (package.version.as_ref().map(|v| v.to_string()), package.reference.clone()))
+                    .collect();
+                picked.len() > 1
+            })
+            .collect()
+    }
+
+
+    // ## Output ##
+
+    fn print_tree(&self, start: &str, inverted: bool) {
+        println!("{}", self.label(start));
+
+        let mut visited = BTreeSet::new();
+        visited.insert(String::from(start));
+        self.print_children(start, inverted, "", &mut visited);
+    }
+
+    fn print_children(&self, node: &str, inverted: bool, prefix: &str, visited: &mut BTreeSet<String>) {
+        let children = match self.edges(inverted).get(node) {
+            Some(children) => children,
+            None => return
+        };
+
+        for (index, child) in children.iter().enumerate() {
+            let last = index + 1 == children.len();
+            let repeated = !visited.insert(child.clone());
+
+            println!("{}{}{}{}",
+                prefix,
+                if last { "└── " } else { "├── " },
+                self.label(child),
+                if repeated { " (*)" } else { "" });
+
+            if !repeated {
+                let prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+                self.print_children(child, inverted, &prefix, visited);
+            }
+        }
+    }
+
+    fn print_duplicates(&self, duplicates: &[Vec<&ResolvedPackage>]) {
+        if duplicates.is_empty() {
+            println!("No source is in the graph at more than one version.");
+            return;
+        }
+
+        for group in duplicates.iter() {
+            for package in group.iter() {
+                self.print_tree(&package.name, true);
+            }
+            println!("");
+        }
+    }
+
+    fn print_dot(&self, start: &str, inverted: bool, duplicates: &[Vec<&ResolvedPackage>]) {
+        let duplicated: BTreeSet<&str> = duplicates.iter()
+            .flat_map(|group| group.iter().map(|p| &p.name[..]))
+            .collect();
+
+        // Only what can be reached from the starting point
+        let mut reachable = BTreeSet::new();
+        let mut pending = vec![String::from(start)];
+        while let Some(node) = pending.pop() {
+            if reachable.insert(node.clone()) {
+                pending.extend(self.edges(inverted).get(&node).cloned().unwrap_or(Vec::new()));
+            }
+        }
+
+        println!("digraph dependencies {{");
+        for node in reachable.iter() {
+            let highlight = if duplicated.contains(&node[..]) { ", color=red" } else { "" };
+            println!("    {} [label={}{}];", dot_id(node), dot_string(self.label(node)), highlight);
+        }
+        for node in reachable.iter() {
+            for child in self.edges(inverted).get(node).map(|c| &c[..]).unwrap_or(&[]) {
+                // Edges always point from dependent to dependency
+                let (from, to) = if inverted { (child, node) } else { (node, child) };
+                println!("    {} -> {};", dot_id(from), dot_id(to));
+            }
+        }
+        println!("}}");
+    }
+}
+
+fn label(package: &ResolvedPackage) -> String {
+    match package.version {
+        Some(ref version) => format!("{} {} ({})", package.name, version, package.source),
+        None => format!("{} ({})", package.name, package.source)
+    }
+}
+
+fn dot_id(node: &str) -> String {
+    if node.is_empty() { String::from("game") } else { dot_string(&format!("pkg_{}", node)) }
+}
+
+fn dot_string(value: &str) -> String {
+    format!("\"{}\"", value.replace("\\", "\\\\").replace("\"", "\\\""))
+}
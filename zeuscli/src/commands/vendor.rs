@@ -0,0 +1,76 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::error::Error;
+use std::path::Path;
+use docopt::Docopt;
+use toml::Value;
+
+use zeus::build::BuildOptions;
+
+use commands;
+
+static USAGE: &'static str = "
+Copy Athena and every dependency into the project so it builds without network access.
+
+Usage:
+    zeus vendor [<dir>] [options]
+
+Options:
+    --crates        Vendor cargo's crates as well, needs cargo vendor
+    --no-config     Leave Zeus.toml alone instead of building from the vendored sources
+
+The directory defaults to `vendor`.
+";
+
+#[derive(RustcDecodable, Debug)]
+struct Flags {
+    arg_dir: Option<String>,
+    flag_crates: bool,
+    flag_no_config: bool
+}
+
+pub fn execute() -> Result<(), Box<Error>> {
+    // Parse in the command line flags
+    let flags: Flags = Docopt::new(USAGE)
+        .and_then(|d| d.decode())
+        .unwrap_or_else(|e| e.exit());
+
+    // Open up the current project
+    let project = try!(commands::open_project());
+    let dir = flags.arg_dir.unwrap_or(String::from("vendor"));
+
+    let resolve = try!(project.vendor(Path::new(&dir), flags.flag_crates));
+    for package in resolve.packages.iter() {
+        match package.version {
+            Some(ref version) => println!("   Vendored {} {} ({})", package.name, version, package.source),
+            None => println!("   Vendored {} ({})", package.name, package.source)
+        }
+    }
+
+    if flags.flag_no_config {
+        return Ok(());
+    }
+
+    // From here on setup and build only use what was just vendored
+    let crates = flags.flag_crates;
+    let project = try!(project.update_manifest(|editor| {
+        try!(editor.set("vendor.directory", Value::String(dir.clone())));
+        editor.set("vendor.crates", Value::Boolean(crates))
+    }));
+    try!(project.write_build_manifest(&BuildOptions::default()));
+    println!("Zeus.toml now builds from {}, remove its [vendor] table to fetch sources again.", dir);
+
+    return Ok(());
+}
@@ -0,0 +1,74 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::error::Error;
+use std::io;
+use std::io::ErrorKind;
+use docopt::Docopt;
+
+use zeus::lockfile;
+use zeus::project::ZeusProjectError;
+
+use commands;
+
+static USAGE: &'static str = "
+Check Athena and every fetched dependency against the checksums in Zeus.lock.
+
+Usage:
+    zeus verify [options]
+
+Options:
+    --update        Record the current checksums instead of failing on changes
+";
+
+#[derive(RustcDecodable, Debug)]
+struct Flags {
+    flag_update: bool
+}
+
+pub fn execute() -> Result<(), Box<Error>> {
+    // Parse in the command line flags
+    let flags: Flags = Docopt::new(USAGE)
+        .and_then(|d| d.decode())
+        .unwrap_or_else(|e| e.exit());
+
+    // Open up the current project
+    let project = try!(commands::open_project());
+
+    // Only what was locked can be verified
+    let lockfile_path = project.get_file_path(lockfile::LOCKFILE);
+    let locked = match try!(lockfile::load(&lockfile_path)) {
+        Some(locked) => locked,
+        None => {
+            let missing = io::Error::new(ErrorKind::NotFound, "nothing is locked yet, run `zeus setup` first");
+            return Err(Box::new(ZeusProjectError::io(&lockfile_path, missing)));
+        }
+    };
+
+    let verified = try!(project.verify(&locked, flags.flag_update));
+    for (before, after) in locked.packages.iter().zip(verified.packages.iter()) {
+        match (&before.checksum, &after.checksum) {
+            (&Some(ref old), &Some(ref new)) if old != new => println!("    Updated {} ({})", after.name, new),
+            (&None, &Some(ref new)) => println!("   Recorded {} ({})", after.name, new),
+            (_, &Some(_)) => println!("   Verified {}", after.name),
+            (_, &None) => {}
+        }
+    }
+
+    if verified != locked {
+        try!(lockfile::save(&lockfile_path, &verified));
+    }
+
+    return Ok(());
+}
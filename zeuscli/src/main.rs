@@ -15,13 +15,17 @@
 extern crate zeus;
 extern crate rustc_serialize;
 extern crate docopt;
+extern crate libc;
 extern crate toml;
 
 mod commands;
+mod progress;
 
 use std::error::Error;
 use docopt::Docopt;
 
+use zeus::project::ZeusProjectError;
+
 static USAGE: &'static str = "
 Athena's project build system.
 
@@ -30,10 +34,24 @@ Usage:
     zeus
 
 Some common zeus commands are:
-    version     Display version info and exit
-    list        Display a list of commands
-    new         Create a new athena project
-    setup       Sets up all athena tools for this project
+    version         Display version info and exit
+    list            Display a list of commands
+    new             Create a new athena project
+    setup           Sets up all athena tools for this project
+    build           Compile this project
+    targets         List the platforms this project can be built for
+    check-manifest  Validate this project's Zeus.toml
+    config          Get or set values in Zeus.toml, or list zeus's settings
+    migrate         Update this project's Zeus.toml to the current format
+    tree            Display this project's dependency graph
+    add             Add a dependency to this project
+    remove          Remove a dependency from this project
+    package         Pack this project into a .zeuspkg archive
+    publish         Publish this project to a package registry
+    search          Search a package registry
+    vendor          Copy all dependencies into the project for offline builds
+    verify          Check fetched sources against the checksums in Zeus.lock
+    doctor          Check this machine is ready to build athena projects
 
 See 'zeus help <command>' for more information on a specific command.
 ";
@@ -55,6 +73,20 @@ fn main() {
         "list" => commands::list::execute(),
         "new" => commands::new::execute(),
         "setup" => commands::setup::execute(),
+        "build" => commands::build::execute(),
+        "targets" => commands::targets::execute(),
+        "check-manifest" => commands::check_manifest::execute(),
+        "config" => commands::config::execute(),
+        "migrate" => commands::migrate::execute(),
+        "tree" => commands::tree::execute(),
+        "add" => commands::add::execute(),
+        "remove" => commands::remove::execute(),
+        "package" => commands::package::execute(),
+        "publish" => commands::publish::execute(),
+        "search" => commands::search::execute(),
+        "vendor" => commands::vendor::execute(),
+        "verify" => commands::verify::execute(),
+        "doctor" => commands::doctor::execute(),
         "" => display_usage(),
         _ => display_not_found()
     };
@@ -64,11 +96,41 @@ fn main() {
         Ok(_) => std::process::exit(0),
         Err(err) => {
             println!("{}", err);
-            std::process::exit(1)
+            std::process::exit(exit_code(&*err))
         }
     }
 }
 
+// Every kind of project error gets its own exit code so scripts can tell them apart
+fn exit_code(err: &Error) -> i32 {
+    match err.downcast_ref::<ZeusProjectError>() {
+        Some(&ZeusProjectError::NotAZeusProject) => 2,
+        Some(&ZeusProjectError::AlreadyExists) => 3,
+        Some(&ZeusProjectError::InvalidPath) => 4,
+        Some(&ZeusProjectError::CorruptedFile(_)) => 5,
+        Some(&ZeusProjectError::Io { .. }) => 6,
+        Some(&ZeusProjectError::CommandFailed { .. }) => 7,
+        Some(&ZeusProjectError::InvalidManifest { .. }) => 8,
+        Some(&ZeusProjectError::InvalidKey(_)) => 9,
+        Some(&ZeusProjectError::MissingKey(_)) => 10,
+        Some(&ZeusProjectError::IncompatibleZeus { .. }) => 11,
+        Some(&ZeusProjectError::UnsupportedManifest(_)) => 12,
+        Some(&ZeusProjectError::InvalidDependency { .. }) => 13,
+        Some(&ZeusProjectError::ResolutionFailed(_)) => 14,
+        Some(&ZeusProjectError::ChecksumMismatch { .. }) => 15,
+        Some(&ZeusProjectError::AlreadyPublished { .. }) => 16,
+        Some(&ZeusProjectError::UnsupportedRegistry(_)) => 17,
+        Some(&ZeusProjectError::ToolNotFound { .. }) => 18,
+        Some(&ZeusProjectError::AuthenticationFailed { .. }) => 19,
+        Some(&ZeusProjectError::InvalidSetting { .. }) => 20,
+        Some(&ZeusProjectError::Offline(_)) => 21,
+        Some(&ZeusProjectError::InvalidProfile { .. }) => 22,
+        Some(&ZeusProjectError::UnknownFeature { .. }) => 23,
+        Some(&ZeusProjectError::InvalidTarget { .. }) => 24,
+        None => 1
+    }
+}
+
 
 // ### Misc Command Handlers ###
 
@@ -0,0 +1,110 @@
+// Copyright 2015 The Athena Developers.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::cell::RefCell;
+use std::io;
+use std::io::Write;
+use libc;
+
+use zeus::progress::{Phase, Progress, ProgressSink, Unit};
+
+static BAR_WIDTH: usize = 30;
+
+/// Draws progress as a bar on stderr, redrawn in place. Without a terminal to redraw in, like
+/// on CI or with stderr going to a file, every change of task is printed as a line of its own instead.
+#[derive(Debug)]
+pub struct ProgressBar {
+    interactive: bool,
+    // The last phase, task and detail shown and how wide the bar line was
+    state: RefCell<(Option<(Phase, String, String)>, usize)>
+}
+
+impl ProgressBar {
+    pub fn new() -> ProgressBar {
+        ProgressBar {
+            interactive: stderr_is_terminal(),
+            state: RefCell::new((None, 0))
+        }
+    }
+}
+
+impl ProgressSink for ProgressBar {
+    fn report(&self, progress: &Progress) {
+        let mut state = self.state.borrow_mut();
+        let key = (progress.phase, progress.task.clone(), progress.detail.clone());
+        let changed = state.0.as_ref() != Some(&key);
+        let mut stderr = io::stderr();
+
+        if !self.interactive {
+            if changed {
+                let _ = writeln!(stderr, "{:>10} {} {}", phase_name(progress.phase), progress.task, progress.detail);
+            }
+            state.0 = Some(key);
+            return;
+        }
+
+        // A new task gets a line of its own, the old one stays as it ended
+        if changed && state.1 != 0 {
+            let _ = writeln!(stderr, "");
+        }
+
+        let line = format!("{:>10} {} {} {}", phase_name(progress.phase), progress.task, bar(progress), progress.detail);
+        let padding = state.1.saturating_sub(line.len());
+        let _ = write!(stderr, "\r{}{}", line, repeat(' ', padding));
+        let _ = stderr.flush();
+
+        state.0 = Some(key);
+        state.1 = line.len();
+    }
+}
+
+impl Drop for ProgressBar {
+    fn drop(&mut self) {
+        if self.interactive && self.state.borrow().1 != 0 {
+            let _ = writeln!(io::stderr(), "");
+        }
+    }
+}
+
+fn stderr_is_terminal() -> bool {
+    unsafe { libc::isatty(2) != 0 }
+}
+
+fn phase_name(phase: Phase) -> &'static str {
+    match phase {
+        Phase::Resolve => "Resolving",
+        Phase::Fetch => "Fetching",
+        Phase::Verify => "Verifying",
+        Phase::Build => "Building"
+    }
+}
+
+fn bar(progress: &Progress) -> String {
+    let percentage = match progress.percentage() {
+        Some(percentage) => percentage as usize,
+        // Nothing to fill a bar with, count instead
+        None => return match progress.unit {
+            Unit::Steps => format!("[{}]", progress.current),
+            Unit::Objects => format!("[{} objects]", progress.current),
+            Unit::Bytes => format!("[{} bytes]", progress.current)
+        }
+    };
+
+    let filled = BAR_WIDTH * percentage / 100;
+    format!("[{}{}] {:>3}%", repeat('=', filled), repeat(' ', BAR_WIDTH - filled), percentage)
+}
+
+fn repeat(c: char, count: usize) -> String {
+    (0..count).map(|_| c).collect()
+}
@@ -29,16 +29,42 @@ use opengl_graphics::{GlGraphics, OpenGL};
 use opengl_graphics::glyph_cache::GlyphCache;
 use piston::event::*;
 use piston::window::{WindowSettings, Size};
+use std::fmt;
 use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::thread;
+use zeus::progress::{Progress, ProgressSink};
 use zeus::project::*;
 
+// What the setup thread tells the window about
+enum GuiEvent {
+    Progress(Progress),
+    Done(String)
+}
+
+// Passes progress from the setup thread on to the window
+struct ChannelProgress(Sender<GuiEvent>);
+
+impl fmt::Debug for ChannelProgress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ChannelProgress")
+    }
+}
+
+impl ProgressSink for ChannelProgress {
+    fn report(&self, progress: &Progress) {
+        let _ = self.0.send(GuiEvent::Progress(progress.clone()));
+    }
+}
+
 fn main() {
     let opengl = OpenGL::_3_2;
     let window = GlutinWindow::new(
         opengl,
         WindowSettings::new(
             "Athena".to_string(),
-            Size { width: 600, height: 200 }
+            Size { width: 600, height: 250 }
         )
         .exit_on_esc(true)
         .samples(4)
@@ -54,9 +80,38 @@ fn main() {
     // UI model variables
     let mut path = String::new();
     let mut status_label = String::new();
+    let mut progress_label = String::new();
+    let mut progress_value = 0u8;
+    let mut setup: Option<Receiver<GuiEvent>> = None;
 
     for event in event_iter {
         ui.handle_event(&event);
+
+        // Catch up on whatever the setup thread did since the last event, a thread that went
+        // away without saying it's done panicked
+        let mut finished = false;
+        if let Some(ref events) = setup {
+            loop {
+                match events.try_recv() {
+                    Ok(GuiEvent::Progress(progress)) => {
+                        progress_value = progress.percentage().unwrap_or(0);
+                        progress_label = format!("{:?} {}: {}", progress.phase, progress.task, progress.detail);
+                    },
+                    Ok(GuiEvent::Done(status)) => {
+                        status_label = status;
+                        finished = true;
+                    },
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        if !finished { status_label = String::from("Setup stopped unexpectedly"); }
+                        finished = true;
+                        break;
+                    }
+                }
+            }
+        }
+        if finished { setup = None; }
+
         if let Some(args) = event.render_args() {
             gl.draw(args.viewport(), |c, gl| {
                 // Draw the background
@@ -86,11 +141,24 @@ fn main() {
                         .set(2, ui);
 
                     Button::new()
-                        .xy(100.0, 0.0)
+                        .xy(0.0, 0.0)
                         .dimensions(100.0, 30.0)
                         .label("Open")
                         .react(|| status_label = open_pressed(&path))
                         .set(3, ui);
+
+                    // Only one setup runs at a time
+                    if setup.is_none() {
+                        Button::new()
+                            .xy(100.0, 0.0)
+                            .dimensions(100.0, 30.0)
+                            .label("Setup")
+                            .react(|| {
+                                status_label = String::from("Setting up...");
+                                setup = Some(setup_pressed(&path));
+                            })
+                            .set(5, ui);
+                    }
                 }
 
                 Label::new(&status_label)
@@ -99,6 +167,15 @@ fn main() {
                     .color(rgb(1.0, 1.0, 1.0))
                     .set(4, ui);
 
+                // Only shows progress, there's nothing to drag
+                if setup.is_some() {
+                    Label::new(&progress_bar(progress_value, &progress_label))
+                        .xy(0.0, -85.0)
+                        .font_size(11)
+                        .color(rgb(1.0, 1.0, 1.0))
+                        .set(6, ui);
+                }
+
                 // Draw our UI
                 ui.draw(c, gl);
             });
@@ -106,6 +183,12 @@ fn main() {
     }
 }
 
+fn progress_bar(percentage: u8, label: &str) -> String {
+    let filled = percentage as usize / 5;
+    let bar: String = (0..20).map(|i| if i < filled { '=' } else { ' ' }).collect();
+    format!("[{}] {:>3}% {}", bar, percentage, label)
+}
+
 fn browse_pressed() -> String {
     unsafe {
         // TODO: Add cleanup code
@@ -159,11 +242,35 @@ fn new_pressed(path: &str) -> String {
 
 fn open_pressed(path: &str) -> String {
     match ZeusProject::open(PathBuf::from(path)) {
+        Ok(ref p) if !p.warnings().is_empty() => String::from(format!("Opened: {} (warning: {})", p.game_name(), p.warnings()[0])),
         Ok(p) => String::from(format!("Opened: {}", p.game_name())),
         Err(e) => String::from(format!("Error: {}", e))
     }
 }
 
+// Projects can't leave the thread they were opened on, so the whole setup runs on its own
+// thread and reports back over a channel
+fn setup_pressed(path: &str) -> Receiver<GuiEvent> {
+    let (sender, receiver) = channel();
+    let path = PathBuf::from(path);
+
+    thread::spawn(move || {
+        let status = match ZeusProject::open(path) {
+            Ok(mut project) => {
+                project.set_progress(Rc::new(ChannelProgress(sender.clone())));
+                match project.setup() {
+                    Ok(_) => String::from(format!("Set up {}", project.game_name())),
+                    Err(e) => String::from(format!("Error: {}", e))
+                }
+            },
+            Err(e) => String::from(format!("Error: {}", e))
+        };
+        let _ = sender.send(GuiEvent::Done(status));
+    });
+
+    receiver
+}
+
 fn check_result(result: winapi::HRESULT) {
     if result < 0 {
         return panic!("Error in winapi call: {:x}", result);